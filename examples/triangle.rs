@@ -65,8 +65,8 @@ impl Renderer for TriangleRenderer {
                 gl::STATIC_DRAW,
             );
 
-            let pos_attrib = gl.GetAttribLocation(program, b"position\0".as_ptr() as *const _);
-            let color_attrib = gl.GetAttribLocation(program, b"color\0".as_ptr() as *const _);
+            let pos_attrib = gl.GetAttribLocation(program, c"position".as_ptr());
+            let color_attrib = gl.GetAttribLocation(program, c"color".as_ptr());
             gl.VertexAttribPointer(
                 pos_attrib as gl::types::GLuint,
                 2,
@@ -95,7 +95,7 @@ impl Renderer for TriangleRenderer {
         }
     }
 
-    fn draw(&mut self) {
+    fn draw(&mut self, _width: Option<u32>, _height: Option<u32>) {
         unsafe {
             self.gl.UseProgram(self.program);
 
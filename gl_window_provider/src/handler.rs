@@ -9,7 +9,7 @@ use glutin::{
 use glutin_winit::GlWindow;
 use winit::{
     dpi::PhysicalSize,
-    event::{DeviceEvent, Event, WindowEvent},
+    event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoopWindowTarget},
     window::{Window, WindowBuilder},
 };
@@ -29,6 +29,8 @@ pub(crate) struct State {
     gl_surface: Surface<WindowSurface>,
     gl_context: PossiblyCurrentContext,
     window: Window,
+    /// Last title taken from the renderer.
+    title: Option<String>,
 }
 
 impl<R> EventsHandler<R>
@@ -75,7 +77,9 @@ where
             .make_current(&gl_surface)
             .unwrap();
 
-        self.renderer.get_or_insert_with(|| R::new(&gl_display));
+        self.renderer
+            .get_or_insert_with(|| R::new(&gl_display))
+            .scale_factor_changed_hook(window.scale_factor());
 
         assert!(self
             .state
@@ -83,6 +87,7 @@ where
                 gl_context,
                 gl_surface,
                 window,
+                title: None,
             })
             .is_none());
     }
@@ -94,6 +99,11 @@ where
     ) {
         match window_event {
             WindowEvent::Resized(size) => self.resize(size),
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.renderer
+                    .as_mut()
+                    .map(|renderer| renderer.scale_factor_changed_hook(scale_factor));
+            }
             WindowEvent::CloseRequested => {
                 control_flow.set_exit();
             }
@@ -112,6 +122,11 @@ where
                     .as_mut()
                     .map(|renderer| renderer.keyboard_input_hook(input));
             }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.renderer
+                    .as_mut()
+                    .map(|renderer| renderer.modifiers_changed_hook(modifiers));
+            }
             WindowEvent::CursorMoved { position, .. } => {
                 self.renderer
                     .as_mut()
@@ -153,14 +168,18 @@ where
     }
 
     fn handle_redraw_event(&mut self) {
-        let Some(State { ref gl_surface, ref gl_context, ref window }) = self.state else {
+        let Some(State { ref gl_surface, ref gl_context, ref window, ref mut title }) = self.state
+        else {
             return;
         };
 
-        self.renderer
-            .as_mut()
-            .unwrap()
-            .draw(gl_surface.width(), gl_surface.height());
+        let renderer = self.renderer.as_mut().unwrap();
+        renderer.draw(gl_surface.width(), gl_surface.height());
+
+        if let Some(next_title) = renderer.title().filter(|next| title.as_ref() != Some(next)) {
+            window.set_title(&next_title);
+            *title = Some(next_title);
+        }
 
         window.request_redraw();
         gl_surface.swap_buffers(gl_context).unwrap();
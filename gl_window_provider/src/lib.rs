@@ -10,7 +10,10 @@ use glutin_winit::DisplayBuilder;
 use raw_window_handle::HasRawWindowHandle;
 use winit::{
     dpi::PhysicalPosition,
-    event::{ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, TouchPhase},
+    event::{
+        ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+        TouchPhase,
+    },
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
     window::{Window, WindowBuilder},
 };
@@ -22,13 +25,17 @@ pub trait Renderer {
     where
         D: GlDisplay;
 
-    fn mouse_input_hook(&mut self, state: ElementState, button: MouseButton) {}
+    fn mouse_input_hook(&mut self, _state: ElementState, _button: MouseButton) {}
 
-    fn mouse_wheel_hook(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {}
+    fn mouse_wheel_hook(&mut self, _delta: MouseScrollDelta, _phase: TouchPhase) {}
 
-    fn keyboard_input_hook(&mut self, input: KeyboardInput) {}
+    fn scale_factor_changed_hook(&mut self, _scale_factor: f64) {}
 
-    fn cursor_move_hook(&mut self, pos: PhysicalPosition<f64>) {}
+    fn keyboard_input_hook(&mut self, _input: KeyboardInput) {}
+
+    fn modifiers_changed_hook(&mut self, _modifiers: ModifiersState) {}
+
+    fn cursor_move_hook(&mut self, _pos: PhysicalPosition<f64>) {}
 
     fn cursor_enter_hook(&mut self) {}
 
@@ -36,6 +43,12 @@ pub trait Renderer {
 
     fn draw(&mut self, width: Option<u32>, height: Option<u32>);
 
+    /// Window title, checked after every frame and applied when it changes. `None` keeps the
+    /// current one.
+    fn title(&self) -> Option<String> {
+        None
+    }
+
     fn resize(&mut self, width: i32, height: i32);
 }
 
@@ -16,7 +16,7 @@ impl ElementsArray {
 
             gl.BufferData(
                 gl::ELEMENT_ARRAY_BUFFER,
-                (indices.len() * mem::size_of::<u16>()) as gl::types::GLsizeiptr,
+                mem::size_of_val(indices) as gl::types::GLsizeiptr,
                 indices.as_ptr() as *const _,
                 gl::DYNAMIC_DRAW,
             );
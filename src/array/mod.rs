@@ -23,5 +23,6 @@ pub(crate) enum Size {
     One = 1isize,
     Two = 2,
     Three = 3,
+    #[allow(dead_code)]
     Four = 4,
 }
@@ -0,0 +1,100 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{camera::CameraView, error::BookmarksError};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct CameraBookmark {
+    pub slot: u8,
+    pub name: String,
+    #[serde(flatten)]
+    pub view: CameraView,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct BookmarksFile {
+    #[serde(default, rename = "bookmark")]
+    bookmarks: Vec<CameraBookmark>,
+}
+
+/// Camera views stored in numbered slots and persisted as TOML.
+pub(crate) struct Bookmarks {
+    path: Option<PathBuf>,
+    bookmarks: Vec<CameraBookmark>,
+}
+
+impl Bookmarks {
+    pub const FILE_NAME: &'static str = "camera_bookmarks.toml";
+
+    /// Loads bookmarks from `path`, a missing file is treated as an empty one.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BookmarksError> {
+        let path = path.as_ref().to_path_buf();
+
+        let bookmarks = match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                toml::from_str::<BookmarksFile>(&content)
+                    .map_err(BookmarksError::Parse)?
+                    .bookmarks
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(BookmarksError::Io(err)),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            bookmarks,
+        })
+    }
+
+    /// Bookmarks that are never written to disk, used when the file couldn't be loaded so
+    /// it doesn't get overwritten.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            bookmarks: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, slot: u8) -> Option<&CameraBookmark> {
+        self.bookmarks.iter().find(|bookmark| bookmark.slot == slot)
+    }
+
+    /// Stores `view` in `slot` keeping the name of a bookmark it replaces, then saves the file.
+    pub fn store(&mut self, slot: u8, view: CameraView) -> Result<&CameraBookmark, BookmarksError> {
+        match self
+            .bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.slot == slot)
+        {
+            Some(bookmark) => bookmark.view = view,
+            None => {
+                self.bookmarks.push(CameraBookmark {
+                    slot,
+                    name: format!("slot {slot}"),
+                    view,
+                });
+                self.bookmarks.sort_by_key(|bookmark| bookmark.slot);
+            }
+        }
+
+        self.save()?;
+        Ok(self.get(slot).unwrap())
+    }
+
+    fn save(&self) -> Result<(), BookmarksError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let content = toml::to_string(&BookmarksFile {
+            bookmarks: self.bookmarks.clone(),
+        })
+        .map_err(BookmarksError::Serialize)?;
+
+        std::fs::write(path, content).map_err(BookmarksError::Io)
+    }
+}
@@ -0,0 +1,315 @@
+use glam::{Mat3, Mat4, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::projection::ProjectionConfig;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Projection {
+    Perspective,
+    Axonometric,
+}
+
+impl Projection {
+    fn blend_factor(self) -> f32 {
+        match self {
+            Self::Perspective => 0.0,
+            Self::Axonometric => 1.0,
+        }
+    }
+}
+
+/// Everything needed to reproduce a view of the figure.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct CameraView {
+    pub polar_angle: f32,
+    pub azimuthal_angle: f32,
+    pub zoom: f32,
+    pub target: Vec3,
+    pub projection: Projection,
+}
+
+#[derive(Clone, Copy)]
+struct CameraState {
+    polar_angle: f32,
+    azimuthal_angle: f32,
+    zoom: f32,
+    target: Vec3,
+    /// `0.0` is a pure perspective projection, `1.0` is a pure axonometric one.
+    projection_blend: f32,
+}
+
+pub(crate) struct Camera {
+    current: CameraState,
+    target: CameraState,
+    projection: Projection,
+    projection_config: ProjectionConfig,
+    zoom_min: f32,
+    zoom_max: f32,
+
+    dragging: bool,
+    drag_delta: Vec2,
+    since_last_drag: f32,
+    /// Angular velocity in radians per second, `x` is polar and `y` is azimuthal.
+    angular_velocity: Vec2,
+    inertia: bool,
+}
+
+impl Camera {
+    pub const ZOOM_MIN: f32 = -20.0;
+    pub const ZOOM_DEFAULT: f32 = -4.5;
+    pub const ZOOM_MAX: f32 = -2.0;
+
+    const POLAR_ANGLE_MAX: f32 = std::f32::consts::FRAC_PI_2 * 0.9;
+    const POLAR_ANGLE_MIN: f32 = -Self::POLAR_ANGLE_MAX;
+
+    /// Time in seconds after which a transition has covered ~63% of the remaining way.
+    const TRANSITION_TIME_CONSTANT: f32 = 0.12;
+    /// Rate in 1/s at which the inertial rotation decays after a drag.
+    const INERTIA_DAMPING: f32 = 3.0;
+    /// Drags that stopped longer ago than this (in seconds) before the release don't spin.
+    const INERTIA_RELEASE_WINDOW: f32 = 0.1;
+    const INERTIA_MIN_VELOCITY: f32 = 1e-3;
+    /// Transitions snap to their target once they get closer than this.
+    const TRANSITION_EPSILON: f32 = 1e-4;
+
+    pub fn new(projection_config: ProjectionConfig) -> Self {
+        let state = CameraState {
+            polar_angle: 0.0,
+            azimuthal_angle: 0.0,
+            zoom: Self::ZOOM_DEFAULT,
+            target: Vec3::ZERO,
+            projection_blend: Projection::Perspective.blend_factor(),
+        };
+
+        Self {
+            current: state,
+            target: state,
+            projection: Projection::Perspective,
+            projection_config,
+            zoom_min: Self::ZOOM_MIN,
+            zoom_max: Self::ZOOM_MAX,
+
+            dragging: false,
+            drag_delta: Vec2::ZERO,
+            since_last_drag: 0.0,
+            angular_velocity: Vec2::ZERO,
+            inertia: true,
+        }
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+        self.target.projection_blend = projection.blend_factor();
+    }
+
+    pub fn set_projection_config(&mut self, projection_config: ProjectionConfig) {
+        self.projection_config = projection_config;
+    }
+
+    /// Limits the zoom to `[min, max]`, moving the camera back inside them if needed.
+    pub fn set_zoom_limits(&mut self, min: f32, max: f32) {
+        self.zoom_min = min;
+        self.zoom_max = max;
+        self.target.zoom = self.target.zoom.clamp(min, max);
+    }
+
+    pub fn zoom_by(&mut self, delta: f32) {
+        self.target.zoom = (self.target.zoom + delta).clamp(self.zoom_min, self.zoom_max);
+    }
+
+    /// Moves the look-at target within the view plane, `delta_x` and `delta_y` are fractions of
+    /// the window height with `y` growing downwards. The point under the cursor on the plane
+    /// through the target follows the cursor.
+    pub fn pan(&mut self, delta_x: f32, delta_y: f32) {
+        let view = self.view_matrix();
+        let right = view.row(0).truncate();
+        let up = view.row(1).truncate();
+        let height = 2.0
+            * self
+                .projection_config
+                .half_height_at(self.current.zoom.abs());
+
+        let offset = (up * delta_y - right * delta_x) * height;
+        self.current.target += offset;
+        self.target.target += offset;
+    }
+
+    /// Starts an eased transition of the look-at target back to the origin.
+    pub fn reset_target(&mut self) {
+        self.target.target = Vec3::ZERO;
+    }
+
+    /// The view the camera is at or is currently moving to.
+    pub fn view(&self) -> CameraView {
+        CameraView {
+            polar_angle: self.target.polar_angle,
+            azimuthal_angle: self.target.azimuthal_angle,
+            zoom: self.target.zoom,
+            target: self.target.target,
+            projection: self.projection,
+        }
+    }
+
+    /// Starts an eased transition to `view`, stopping any inertial rotation.
+    pub fn go_to(&mut self, view: CameraView) {
+        self.angular_velocity = Vec2::ZERO;
+        self.projection = view.projection;
+
+        self.target = CameraState {
+            polar_angle: view
+                .polar_angle
+                .clamp(Self::POLAR_ANGLE_MIN, Self::POLAR_ANGLE_MAX),
+            azimuthal_angle: view.azimuthal_angle % (std::f32::consts::PI * 2.0),
+            zoom: view.zoom.clamp(self.zoom_min, self.zoom_max),
+            target: view.target,
+            projection_blend: view.projection.blend_factor(),
+        };
+    }
+
+    pub fn toggle_inertia(&mut self) {
+        self.inertia = !self.inertia;
+        self.angular_velocity = Vec2::ZERO;
+    }
+
+    pub fn begin_drag(&mut self) {
+        self.dragging = true;
+        self.drag_delta = Vec2::ZERO;
+        self.since_last_drag = 0.0;
+        self.angular_velocity = Vec2::ZERO;
+    }
+
+    pub fn end_drag(&mut self) {
+        if !self.dragging {
+            return;
+        }
+
+        self.dragging = false;
+
+        if !self.inertia || self.since_last_drag > Self::INERTIA_RELEASE_WINDOW {
+            self.angular_velocity = Vec2::ZERO;
+        }
+    }
+
+    /// Rotates the camera immediately, the drag speed is sampled in [`Camera::update`].
+    pub fn rotate(&mut self, delta_polar_angle: f32, delta_azimuthal_angle: f32) {
+        let delta = Vec2::new(delta_polar_angle, delta_azimuthal_angle);
+
+        self.drag_delta += delta;
+        self.since_last_drag = 0.0;
+        self.apply_rotation(delta);
+    }
+
+    /// Advances transitions and the inertial rotation by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        if dt <= 0.0 {
+            return;
+        }
+
+        if self.dragging {
+            let velocity = self.drag_delta / dt;
+            self.angular_velocity = self.angular_velocity.lerp(velocity, 0.5);
+            self.drag_delta = Vec2::ZERO;
+            self.since_last_drag += dt;
+        } else if self.inertia && self.angular_velocity.length() > Self::INERTIA_MIN_VELOCITY {
+            self.apply_rotation(self.angular_velocity * dt);
+            self.angular_velocity *= (-Self::INERTIA_DAMPING * dt).exp();
+        } else {
+            self.angular_velocity = Vec2::ZERO;
+        }
+
+        let t = 1.0 - (-dt / Self::TRANSITION_TIME_CONSTANT).exp();
+
+        let (current, target) = (&mut self.current, &self.target);
+
+        let azimuthal_delta =
+            shortest_angle_between(current.azimuthal_angle, target.azimuthal_angle);
+        if azimuthal_delta.abs() < Self::TRANSITION_EPSILON {
+            current.azimuthal_angle = target.azimuthal_angle;
+        } else {
+            current.azimuthal_angle += azimuthal_delta * t;
+        }
+
+        approach(&mut current.polar_angle, target.polar_angle, t);
+        approach(&mut current.zoom, target.zoom, t);
+        approach(&mut current.target.x, target.target.x, t);
+        approach(&mut current.target.y, target.target.y, t);
+        approach(&mut current.target.z, target.target.z, t);
+        approach(&mut current.projection_blend, target.projection_blend, t);
+    }
+
+    pub fn projection_matrix(&self, width: u32, height: u32) -> Mat4 {
+        self.projection_config.blended(
+            width as f32 / height as f32,
+            self.current.zoom.abs(),
+            self.current.projection_blend,
+        )
+    }
+
+    /// Maps clip space to world space view directions. Stays in perspective during axonometric
+    /// projections, their parallel rays would fill the background with a single color.
+    pub fn skybox_matrix(&self, width: u32, height: u32) -> Mat4 {
+        let rotation = Mat4::from_mat3(Mat3::from_mat4(self.view_matrix()));
+        let projection = self
+            .projection_config
+            .perspective(width as f32 / height as f32);
+
+        (projection * rotation).inverse()
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_at_lh(self.view_pos(), self.current.target, Vec3::Y)
+    }
+
+    pub fn view_pos(&self) -> Vec3 {
+        let CameraState {
+            polar_angle,
+            azimuthal_angle,
+            zoom,
+            target,
+            ..
+        } = self.current;
+
+        target
+            + Vec3::new(
+                zoom * polar_angle.cos() * azimuthal_angle.sin(),
+                zoom * polar_angle.sin(),
+                zoom * polar_angle.cos() * azimuthal_angle.cos(),
+            )
+    }
+
+    fn apply_rotation(&mut self, delta: Vec2) {
+        let polar_angle = (self.current.polar_angle + delta.x)
+            .clamp(Self::POLAR_ANGLE_MIN, Self::POLAR_ANGLE_MAX);
+        let azimuthal_angle =
+            (self.current.azimuthal_angle + delta.y) % (std::f32::consts::PI * 2.0);
+
+        self.current.polar_angle = polar_angle;
+        self.current.azimuthal_angle = azimuthal_angle;
+        self.target.polar_angle = polar_angle;
+        self.target.azimuthal_angle = azimuthal_angle;
+    }
+}
+
+/// Moves `value` towards `target` by the fraction `t`, snapping once it's close enough.
+fn approach(value: &mut f32, target: f32, t: f32) {
+    if (target - *value).abs() < Camera::TRANSITION_EPSILON {
+        *value = target;
+    } else {
+        *value += (target - *value) * t;
+    }
+}
+
+/// Signed difference `to - from` wrapped into `[-PI, PI]`.
+fn shortest_angle_between(from: f32, to: f32) -> f32 {
+    let tau = std::f32::consts::PI * 2.0;
+    let delta = (to - from) % tau;
+
+    if delta > std::f32::consts::PI {
+        delta - tau
+    } else if delta < -std::f32::consts::PI {
+        delta + tau
+    } else {
+        delta
+    }
+}
@@ -0,0 +1,90 @@
+use std::{path::PathBuf, sync::OnceLock};
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+/// Command line arguments, set once in `main` before the renderer is created.
+#[derive(Default)]
+pub(crate) struct Args {
+    /// Overrides the diffuse map of the scene file.
+    pub texture: Option<PathBuf>,
+}
+
+impl Args {
+    pub const USAGE: &'static str = "usage: graphics_rgz [--texture <path>]";
+
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--texture" => {
+                    let path = args.next().ok_or("--texture expects a path")?;
+                    parsed.texture = Some(path.into());
+                }
+                _ => return Err(format!("unexpected argument \"{arg}\"")),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn set(self) {
+        // `main` calls this once, before anything reads the arguments.
+        let _ = ARGS.set(self);
+    }
+
+    pub fn get() -> &'static Self {
+        ARGS.get_or_init(Self::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::Args;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments() {
+        assert!(parse(&[]).unwrap().texture.is_none());
+    }
+
+    #[test]
+    fn texture() {
+        let args = parse(&["--texture", "textures/brick.png"]).unwrap();
+
+        assert_eq!(
+            args.texture.as_deref(),
+            Some(Path::new("textures/brick.png"))
+        );
+    }
+
+    #[test]
+    fn last_texture_wins() {
+        let args = parse(&["--texture", "first.png", "--texture", "second.png"]).unwrap();
+
+        assert_eq!(args.texture.as_deref(), Some(Path::new("second.png")));
+    }
+
+    #[test]
+    fn texture_without_path() {
+        assert_eq!(
+            parse(&["--texture"]).err().unwrap(),
+            "--texture expects a path"
+        );
+    }
+
+    #[test]
+    fn unknown_argument() {
+        assert_eq!(
+            parse(&["--texture", "brick.png", "--verbose"])
+                .err()
+                .unwrap(),
+            "unexpected argument \"--verbose\""
+        );
+    }
+}
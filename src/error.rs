@@ -26,3 +26,91 @@ impl std::fmt::Display for GlError {
 }
 
 impl std::error::Error for GlError {}
+
+#[derive(Debug)]
+pub enum BookmarksError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl std::fmt::Display for BookmarksError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to access bookmarks file: {err}"),
+            Self::Parse(err) => write!(f, "failed to parse bookmarks file: {err}"),
+            Self::Serialize(err) => write!(f, "failed to serialize bookmarks: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for BookmarksError {}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    /// Every value of the file that is out of range, as `key: problem`.
+    Invalid(Vec<String>),
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read scene file: {err}"),
+            Self::Parse(err) => write!(f, "failed to parse scene file: {err}"),
+            Self::Invalid(problems) => {
+                write!(f, "invalid scene file:")?;
+                for problem in problems {
+                    write!(f, "\n  {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Open {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    Decode {
+        path: std::path::PathBuf,
+        source: image::ImageError,
+    },
+    /// A cube map face that isn't square or doesn't match the size of the first face.
+    FaceSize {
+        face: &'static str,
+        width: u32,
+        height: u32,
+        size: u32,
+    },
+}
+
+impl std::fmt::Display for TextureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open { path, source } => {
+                write!(f, "failed to open texture {}: {source}", path.display())
+            }
+            Self::Decode { path, source } => {
+                write!(f, "failed to decode texture {}: {source}", path.display())
+            }
+            Self::FaceSize {
+                face,
+                width,
+                height,
+                size,
+            } => write!(
+                f,
+                "cube map face {face} is {width}x{height}, all faces must be {size}x{size}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
@@ -0,0 +1,71 @@
+use crate::texture::{DepthCubemap, DepthTexture};
+
+/// Framebuffer object rendering into a depth texture only, without any color attachment.
+pub(crate) struct DepthFramebuffer {
+    id: gl::types::GLuint,
+    gl: gl::Gl,
+}
+
+impl DepthFramebuffer {
+    pub fn new(gl: gl::Gl) -> Self {
+        let mut id = 0;
+
+        unsafe {
+            gl.GenFramebuffers(1, &mut id);
+            gl.BindFramebuffer(gl::FRAMEBUFFER, id);
+            gl.DrawBuffers(1, [gl::NONE].as_ptr());
+            gl.ReadBuffer(gl::NONE);
+            gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self { id, gl }
+    }
+
+    /// Binds the framebuffer with `texture` as the depth target and sets the viewport to it.
+    pub fn bind_texture(&self, texture: &DepthTexture) {
+        unsafe {
+            self.gl.BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            self.gl.FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_2D,
+                texture.id(),
+                0,
+            );
+            self.gl
+                .Viewport(0, 0, texture.size() as i32, texture.size() as i32);
+        }
+    }
+
+    /// Binds the framebuffer with one face of `cubemap` as the depth target, `face` follows the
+    /// `TEXTURE_CUBE_MAP_POSITIVE_X` order.
+    pub fn bind_cubemap_face(&self, cubemap: &DepthCubemap, face: u32) {
+        unsafe {
+            self.gl.BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            self.gl.FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                cubemap.id(),
+                0,
+            );
+            self.gl
+                .Viewport(0, 0, cubemap.size() as i32, cubemap.size() as i32);
+        }
+    }
+
+    /// Switches back to the default framebuffer.
+    pub fn unbind(&self) {
+        unsafe {
+            self.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+}
+
+impl Drop for DepthFramebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteFramebuffers(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use serde::Deserialize;
+use winit::event::{MouseScrollDelta, TouchPhase};
+
+/// Mouse and touchpad sensitivities, independent of the window size and the display DPI.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct InputConfig {
+    /// Rotation in radians for dragging the cursor across the whole window height.
+    pub rotation_sensitivity: f32,
+    /// Zoom change per scrolled line.
+    pub zoom_sensitivity: f32,
+    /// How many logical pixels of a touchpad scroll make up one line.
+    pub pixels_per_line: f32,
+}
+
+impl InputConfig {
+    /// Converts a cursor movement in physical pixels into polar and azimuthal angle deltas.
+    ///
+    /// Both axes are normalized by the window height, so a pixel turns the camera by the same
+    /// angle horizontally and vertically whatever the window aspect ratio is.
+    pub fn rotation_delta(&self, delta_x: f64, delta_y: f64, window_height: u32) -> (f32, f32) {
+        let factor = self.rotation_sensitivity / window_height.max(1) as f32;
+
+        (delta_y as f32 * factor, delta_x as f32 * factor)
+    }
+
+    /// Converts a wheel or touchpad scroll into a zoom delta, `None` for cancelled gestures.
+    pub fn zoom_delta(
+        &self,
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+        scale_factor: f64,
+    ) -> Option<f32> {
+        if let TouchPhase::Cancelled = phase {
+            return None;
+        }
+
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, vertical_delta) => vertical_delta,
+            MouseScrollDelta::PixelDelta(position) => {
+                let logical_delta = position.y / scale_factor;
+                logical_delta as f32 / self.pixels_per_line
+            }
+        };
+
+        Some(lines * self.zoom_sensitivity)
+    }
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            rotation_sensitivity: std::f32::consts::PI,
+            zoom_sensitivity: 0.1,
+            pixels_per_line: 20.0,
+        }
+    }
+}
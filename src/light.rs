@@ -0,0 +1,372 @@
+use std::sync::OnceLock;
+
+use glam::Vec3;
+use serde::Deserialize;
+
+use crate::{material::authored_color, shader_program::ShaderProgram};
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct DirLight {
+    pub direction: Vec3,
+    /// Irradiance in W/m², used by the PBR material mode only.
+    pub intensity: f32,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct PointLight {
+    pub position: Vec3,
+    /// Radiant intensity in W/sr, used by the PBR material mode only.
+    pub intensity: f32,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+/// Point light emitting only inside a cone, fading out between the inner and outer cutoff.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    /// Half-angle in radians of the cone lit at full intensity.
+    pub inner_cutoff: f32,
+    /// Half-angle in radians beyond which nothing is lit.
+    pub outer_cutoff: f32,
+    /// Radiant intensity in W/sr, used by the PBR material mode only.
+    pub intensity: f32,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for DirLight {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::new(0.0, 1.0, 0.0),
+            intensity: 2.0,
+            ambient: Vec3::splat(0.1),
+            diffuse: Vec3::splat(0.2),
+            specular: Vec3::splat(0.4),
+        }
+    }
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 4.0,
+            ambient: Vec3::splat(0.1),
+            diffuse: Vec3::splat(0.4),
+            specular: Vec3::splat(0.5),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+        }
+    }
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(0.0, 2.5, -2.5),
+            direction: Vec3::new(0.0, -1.0, 1.0),
+            inner_cutoff: 0.21,
+            outer_cutoff: 0.3,
+            intensity: 20.0,
+            ambient: Vec3::splat(0.0),
+            diffuse: Vec3::splat(0.8),
+            specular: Vec3::splat(1.0),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+        }
+    }
+}
+
+/// Lights of one kind, each of them can be switched off without removing it.
+pub(crate) struct LightList<T, const MAX: usize> {
+    lights: Vec<(T, bool)>,
+}
+
+impl<T, const MAX: usize> LightList<T, MAX> {
+    /// Adds an enabled light and returns its index, `None` if the list is full.
+    pub fn add(&mut self, light: T) -> Option<usize> {
+        if self.lights.len() == MAX {
+            return None;
+        }
+
+        self.lights.push((light, true));
+        Some(self.lights.len() - 1)
+    }
+
+    /// Removes the light at `index`, the lights after it shift down by one.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        (index < self.lights.len()).then(|| self.lights.remove(index).0)
+    }
+
+    /// Switches the light at `index` on or off and returns its new state.
+    pub fn toggle(&mut self, index: usize) -> Option<bool> {
+        self.lights.get_mut(index).map(|(_, enabled)| {
+            *enabled = !*enabled;
+            *enabled
+        })
+    }
+
+    /// Switches every light on or off.
+    pub fn set_all(&mut self, enabled: bool) {
+        for (_, light_enabled) in &mut self.lights {
+            *light_enabled = enabled;
+        }
+    }
+
+    pub fn any_enabled(&self) -> bool {
+        self.lights.iter().any(|(_, enabled)| *enabled)
+    }
+
+    pub fn get(&self, index: usize) -> Option<(&T, bool)> {
+        self.lights
+            .get(index)
+            .map(|(light, enabled)| (light, *enabled))
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.lights.get_mut(index).map(|(light, _)| light)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    pub fn enabled(&self) -> impl Iterator<Item = &T> {
+        self.lights
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(light, _)| light)
+    }
+}
+
+impl<T, const MAX: usize> Default for LightList<T, MAX> {
+    fn default() -> Self {
+        Self { lights: Vec::new() }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum LightKind {
+    Directional,
+    Point,
+    Spot,
+}
+
+/// Refers to a light by its kind and index in the matching [`LightList`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct LightHandle {
+    pub kind: LightKind,
+    pub index: usize,
+}
+
+/// Limits must match `MAX_DIR_LIGHTS`, `MAX_POINT_LIGHTS` and `MAX_SPOT_LIGHTS` in
+/// `polygon_program/lighting.glsl`.
+pub(crate) const MAX_DIR_LIGHTS: usize = 4;
+pub(crate) const MAX_POINT_LIGHTS: usize = 8;
+pub(crate) const MAX_SPOT_LIGHTS: usize = 4;
+
+#[derive(Default)]
+pub(crate) struct Lights {
+    pub directional: LightList<DirLight, MAX_DIR_LIGHTS>,
+    pub point: LightList<PointLight, MAX_POINT_LIGHTS>,
+    pub spot: LightList<SpotLight, MAX_SPOT_LIGHTS>,
+}
+
+impl Lights {
+    /// All lights in the order directional, point, spot.
+    pub fn handles(&self) -> impl Iterator<Item = LightHandle> {
+        let handles = |kind, len| (0..len).map(move |index| LightHandle { kind, index });
+
+        handles(LightKind::Directional, self.directional.len())
+            .chain(handles(LightKind::Point, self.point.len()))
+            .chain(handles(LightKind::Spot, self.spot.len()))
+    }
+
+    pub fn contains(&self, handle: LightHandle) -> bool {
+        let len = match handle.kind {
+            LightKind::Directional => self.directional.len(),
+            LightKind::Point => self.point.len(),
+            LightKind::Spot => self.spot.len(),
+        };
+
+        handle.index < len
+    }
+
+    /// The light following `current` in [`Lights::handles`] order, wrapping around.
+    pub fn next(&self, current: Option<LightHandle>) -> Option<LightHandle> {
+        let mut handles = self.handles();
+
+        match current {
+            Some(current) => handles
+                .by_ref()
+                .position(|handle| handle == current)
+                .and_then(|_| handles.next())
+                .or_else(|| self.handles().next()),
+            None => handles.next(),
+        }
+    }
+
+    /// Sets the uniforms of a program including `lighting.glsl`, the colors are converted with
+    /// [`authored_color`].
+    pub fn upload(&self, program: &ShaderProgram, gamma_correction: bool) {
+        let color = |color| authored_color(color, gamma_correction).to_array();
+        let uniforms = LightUniforms::get();
+
+        let mut count = 0;
+        for (light, name) in self.directional.enabled().zip(&uniforms.directional) {
+            program.set_uniform_vec3(&name.direction, light.direction.to_array());
+            program.set_uniform_f32(&name.intensity, light.intensity);
+            program.set_uniform_vec3(&name.ambient, color(light.ambient));
+            program.set_uniform_vec3(&name.diffuse, color(light.diffuse));
+            program.set_uniform_vec3(&name.specular, color(light.specular));
+            count += 1;
+        }
+        program.set_uniform_i32("uDirLightsCount", count);
+
+        let mut count = 0;
+        for (light, name) in self.point.enabled().zip(&uniforms.point) {
+            program.set_uniform_vec3(&name.position, light.position.to_array());
+            program.set_uniform_f32(&name.intensity, light.intensity);
+            program.set_uniform_vec3(&name.ambient, color(light.ambient));
+            program.set_uniform_vec3(&name.diffuse, color(light.diffuse));
+            program.set_uniform_vec3(&name.specular, color(light.specular));
+            program.set_uniform_f32(&name.constant, light.constant);
+            program.set_uniform_f32(&name.linear, light.linear);
+            program.set_uniform_f32(&name.quadratic, light.quadratic);
+            count += 1;
+        }
+        program.set_uniform_i32("uPointLightsCount", count);
+
+        let mut count = 0;
+        for (light, name) in self.spot.enabled().zip(&uniforms.spot) {
+            program.set_uniform_vec3(&name.position, light.position.to_array());
+            program.set_uniform_vec3(&name.direction, light.direction.to_array());
+            program.set_uniform_f32(&name.inner_cutoff, light.inner_cutoff.cos());
+            program.set_uniform_f32(&name.outer_cutoff, light.outer_cutoff.cos());
+            program.set_uniform_f32(&name.intensity, light.intensity);
+            program.set_uniform_vec3(&name.ambient, color(light.ambient));
+            program.set_uniform_vec3(&name.diffuse, color(light.diffuse));
+            program.set_uniform_vec3(&name.specular, color(light.specular));
+            program.set_uniform_f32(&name.constant, light.constant);
+            program.set_uniform_f32(&name.linear, light.linear);
+            program.set_uniform_f32(&name.quadratic, light.quadratic);
+            count += 1;
+        }
+        program.set_uniform_i32("uSpotLightsCount", count);
+    }
+}
+
+/// Uniform names of the light arrays in `lighting.glsl`, formatted once rather than every frame.
+struct LightUniforms {
+    directional: [LightFieldNames; MAX_DIR_LIGHTS],
+    point: [LightFieldNames; MAX_POINT_LIGHTS],
+    spot: [LightFieldNames; MAX_SPOT_LIGHTS],
+}
+
+impl LightUniforms {
+    fn get() -> &'static Self {
+        static UNIFORMS: OnceLock<LightUniforms> = OnceLock::new();
+
+        UNIFORMS.get_or_init(|| Self {
+            directional: LightFieldNames::array("uDirLights"),
+            point: LightFieldNames::array("uPointLights"),
+            spot: LightFieldNames::array("uSpotLights"),
+        })
+    }
+}
+
+/// Names of the fields of one light struct, each kind only uses some of them.
+struct LightFieldNames {
+    position: String,
+    direction: String,
+    inner_cutoff: String,
+    outer_cutoff: String,
+    intensity: String,
+    ambient: String,
+    diffuse: String,
+    specular: String,
+    constant: String,
+    linear: String,
+    quadratic: String,
+}
+
+impl LightFieldNames {
+    fn array<const N: usize>(array: &str) -> [Self; N] {
+        std::array::from_fn(|index| {
+            let field = |field| format!("{array}[{index}].{field}");
+
+            Self {
+                position: field("position"),
+                direction: field("direction"),
+                inner_cutoff: field("innerCutOff"),
+                outer_cutoff: field("outerCutOff"),
+                intensity: field("intensity"),
+                ambient: field("ambient"),
+                diffuse: field("diffuse"),
+                specular: field("specular"),
+                constant: field("constant"),
+                linear: field("linear"),
+                quadratic: field("quadratic"),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LightList;
+
+    #[test]
+    fn toggle_switches_a_single_light() {
+        let mut list = LightList::<u32, 2>::default();
+        list.add(1);
+        list.add(2);
+
+        assert_eq!(list.toggle(1), Some(false));
+        assert_eq!(list.enabled().copied().collect::<Vec<_>>(), [1]);
+        assert_eq!(list.toggle(1), Some(true));
+        assert_eq!(list.toggle(2), None);
+    }
+
+    #[test]
+    fn set_all_after_toggle() {
+        let mut list = LightList::<u32, 2>::default();
+        list.add(1);
+        list.add(2);
+        list.toggle(0);
+        assert!(list.any_enabled());
+
+        list.set_all(false);
+        assert!(!list.any_enabled());
+        list.set_all(true);
+        assert_eq!(list.enabled().count(), 2);
+    }
+
+    #[test]
+    fn add_stops_at_the_limit() {
+        let mut list = LightList::<u32, 1>::default();
+
+        assert_eq!(list.add(1), Some(0));
+        assert_eq!(list.add(2), None);
+        assert_eq!(list.remove(0), Some(1));
+        assert_eq!(list.len(), 0);
+    }
+}
@@ -0,0 +1,255 @@
+use glam::Vec3;
+
+use crate::light::{LightHandle, LightKind, Lights};
+
+/// Marker drawn for a light while the editor is active.
+pub(crate) struct LightMarker {
+    pub position: Vec3,
+    pub color: Vec3,
+    pub selected: bool,
+}
+
+/// Moves, aims and recolors the lights in light-edit mode.
+pub(crate) struct LightEditor {
+    active: bool,
+    selected: Option<LightHandle>,
+    color_index: usize,
+}
+
+impl LightEditor {
+    /// Distance from the origin at which directional lights get their marker.
+    const DIR_LIGHT_MARKER_DISTANCE: f32 = 2.5;
+    const DISABLED_MARKER_COLOR: Vec3 = Vec3::splat(0.3);
+
+    const POLAR_ANGLE_MAX: f32 = std::f32::consts::FRAC_PI_2 * 0.99;
+    const ATTENUATION_FACTOR: f32 = 1.25;
+
+    const COLORS: [Vec3; 6] = [
+        Vec3::new(1.0, 1.0, 1.0),
+        Vec3::new(1.0, 0.85, 0.6),
+        Vec3::new(0.6, 0.8, 1.0),
+        Vec3::new(1.0, 0.2, 0.2),
+        Vec3::new(0.2, 1.0, 0.2),
+        Vec3::new(0.2, 0.2, 1.0),
+    ];
+
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            selected: None,
+            color_index: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn toggle(&mut self, lights: &Lights) {
+        self.active = !self.active;
+
+        if self.active {
+            self.selected = self.selected(lights).or_else(|| lights.next(None));
+            self.report_selection();
+        }
+    }
+
+    pub fn select_next(&mut self, lights: &Lights) {
+        self.selected = lights.next(self.selected(lights));
+        self.report_selection();
+    }
+
+    /// Orbits the selected light around the origin, directional lights are turned instead.
+    /// Spotlights are re-aimed at the origin.
+    pub fn orbit(
+        &mut self,
+        lights: &mut Lights,
+        delta_polar_angle: f32,
+        delta_azimuthal_angle: f32,
+    ) {
+        let Some(handle) = self.selected(lights) else {
+            return;
+        };
+
+        let orbit = |vector: Vec3| orbit(vector, delta_polar_angle, delta_azimuthal_angle);
+
+        match handle.kind {
+            LightKind::Directional => {
+                let light = lights.directional.get_mut(handle.index).unwrap();
+                light.direction = -orbit(-light.direction);
+            }
+            LightKind::Point => {
+                let light = lights.point.get_mut(handle.index).unwrap();
+                light.position = orbit(light.position);
+            }
+            LightKind::Spot => {
+                let light = lights.spot.get_mut(handle.index).unwrap();
+                light.position = orbit(light.position);
+                light.direction = -light.position;
+            }
+        }
+    }
+
+    /// Moves the selected light by `offset`, directional lights are tilted towards it instead.
+    pub fn translate(&mut self, lights: &mut Lights, offset: Vec3) {
+        let Some(handle) = self.selected(lights) else {
+            return;
+        };
+
+        match handle.kind {
+            LightKind::Directional => {
+                let light = lights.directional.get_mut(handle.index).unwrap();
+                let length = light.direction.length();
+                light.direction = (light.direction.normalize() - offset).normalize() * length;
+            }
+            LightKind::Point => lights.point.get_mut(handle.index).unwrap().position += offset,
+            LightKind::Spot => lights.spot.get_mut(handle.index).unwrap().position += offset,
+        }
+    }
+
+    /// Switches the selected light on or off, it stays selected either way.
+    pub fn toggle_selected(&mut self, lights: &mut Lights) {
+        let Some(handle) = self.selected(lights) else {
+            return;
+        };
+
+        let enabled = match handle.kind {
+            LightKind::Directional => lights.directional.toggle(handle.index),
+            LightKind::Point => lights.point.toggle(handle.index),
+            LightKind::Spot => lights.spot.toggle(handle.index),
+        };
+        if let Some(enabled) = enabled {
+            println!("{:?} light #{}: {enabled}", handle.kind, handle.index);
+        }
+    }
+
+    /// Gives the selected light the next color of the palette, keeping its intensities.
+    pub fn cycle_color(&mut self, lights: &mut Lights) {
+        let Some(handle) = self.selected(lights) else {
+            return;
+        };
+
+        self.color_index = (self.color_index + 1) % Self::COLORS.len();
+        let color = Self::COLORS[self.color_index];
+        let recolor = |value: &mut Vec3| *value = color * value.max_element();
+
+        match handle.kind {
+            LightKind::Directional => {
+                let light = lights.directional.get_mut(handle.index).unwrap();
+                recolor(&mut light.ambient);
+                recolor(&mut light.diffuse);
+                recolor(&mut light.specular);
+            }
+            LightKind::Point => {
+                let light = lights.point.get_mut(handle.index).unwrap();
+                recolor(&mut light.ambient);
+                recolor(&mut light.diffuse);
+                recolor(&mut light.specular);
+            }
+            LightKind::Spot => {
+                let light = lights.spot.get_mut(handle.index).unwrap();
+                recolor(&mut light.ambient);
+                recolor(&mut light.diffuse);
+                recolor(&mut light.specular);
+            }
+        }
+    }
+
+    /// Makes the selected light fall off faster (`stronger`) or reach farther.
+    pub fn scale_attenuation(&mut self, lights: &mut Lights, stronger: bool) {
+        let Some(handle) = self.selected(lights) else {
+            return;
+        };
+
+        let factor = if stronger {
+            Self::ATTENUATION_FACTOR
+        } else {
+            Self::ATTENUATION_FACTOR.recip()
+        };
+
+        let (linear, quadratic) = match handle.kind {
+            LightKind::Directional => return,
+            LightKind::Point => {
+                let light = lights.point.get_mut(handle.index).unwrap();
+                (&mut light.linear, &mut light.quadratic)
+            }
+            LightKind::Spot => {
+                let light = lights.spot.get_mut(handle.index).unwrap();
+                (&mut light.linear, &mut light.quadratic)
+            }
+        };
+
+        *linear *= factor;
+        *quadratic *= factor;
+        println!("attenuation: linear {linear:.4}, quadratic {quadratic:.4}");
+    }
+
+    pub fn markers(&self, lights: &Lights) -> Vec<LightMarker> {
+        let selected = self.selected(lights);
+
+        lights
+            .handles()
+            .map(|handle| {
+                let (position, diffuse, enabled) = match handle.kind {
+                    LightKind::Directional => {
+                        let (light, enabled) = lights.directional.get(handle.index).unwrap();
+                        let position =
+                            -light.direction.normalize() * Self::DIR_LIGHT_MARKER_DISTANCE;
+                        (position, light.diffuse, enabled)
+                    }
+                    LightKind::Point => {
+                        let (light, enabled) = lights.point.get(handle.index).unwrap();
+                        (light.position, light.diffuse, enabled)
+                    }
+                    LightKind::Spot => {
+                        let (light, enabled) = lights.spot.get(handle.index).unwrap();
+                        (light.position, light.diffuse, enabled)
+                    }
+                };
+
+                let color = if enabled && diffuse.max_element() > 0.0 {
+                    diffuse / diffuse.max_element()
+                } else {
+                    Self::DISABLED_MARKER_COLOR
+                };
+
+                LightMarker {
+                    position,
+                    color,
+                    selected: Some(handle) == selected,
+                }
+            })
+            .collect()
+    }
+
+    /// The selected light, if it still exists.
+    fn selected(&self, lights: &Lights) -> Option<LightHandle> {
+        self.selected.filter(|&handle| lights.contains(handle))
+    }
+
+    fn report_selection(&self) {
+        match self.selected {
+            Some(LightHandle { kind, index }) => println!("editing {kind:?} light #{index}"),
+            None => println!("there are no lights to edit"),
+        }
+    }
+}
+
+/// Rotates `vector` around the origin using the same angles as the orbit camera.
+fn orbit(vector: Vec3, delta_polar_angle: f32, delta_azimuthal_angle: f32) -> Vec3 {
+    let radius = vector.length();
+    if radius == 0.0 {
+        return vector;
+    }
+
+    let polar_angle = ((vector.y / radius).asin() + delta_polar_angle)
+        .clamp(-LightEditor::POLAR_ANGLE_MAX, LightEditor::POLAR_ANGLE_MAX);
+    let azimuthal_angle = vector.x.atan2(vector.z) + delta_azimuthal_angle;
+
+    radius
+        * Vec3::new(
+            polar_angle.cos() * azimuthal_angle.sin(),
+            polar_angle.sin(),
+            polar_angle.cos() * azimuthal_angle.cos(),
+        )
+}
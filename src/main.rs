@@ -2,11 +2,27 @@ use gl_window_provider::GlWindowProvider;
 use renderer::RgzRenderer;
 use winit::event_loop::EventLoop;
 
+mod array;
+mod bookmarks;
+mod camera;
+mod cli;
 mod error;
+mod framebuffer;
+mod input;
+mod light;
+mod light_editor;
+mod material;
+mod procedural;
+mod projection;
 mod renderer;
+mod scene;
 mod shader_program;
-mod array;
+mod shadow;
+mod skybox;
 mod texture;
+mod texture_data;
+mod uv;
+mod watcher;
 
 #[derive(Debug)]
 pub enum ShaderKind {
@@ -26,6 +42,14 @@ impl std::fmt::Display for ShaderKind {
 }
 
 fn main() {
+    match cli::Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args.set(),
+        Err(err) => {
+            eprintln!("{err}\n{}", cli::Args::USAGE);
+            std::process::exit(2);
+        }
+    }
+
     let event_loop = EventLoop::new();
     let handler = GlWindowProvider::new(&event_loop).build_handler::<RgzRenderer, ()>();
     event_loop.run(handler);
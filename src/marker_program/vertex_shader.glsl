@@ -0,0 +1,11 @@
+#version 330 core
+
+in vec3 iPosition;
+
+uniform mat4 uModelMat;
+uniform mat4 uViewMat;
+uniform mat4 uProjectionMat;
+
+void main() {
+  gl_Position = uProjectionMat * uViewMat * uModelMat * vec4(iPosition, 1.0);
+}
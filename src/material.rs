@@ -0,0 +1,574 @@
+use std::path::Path;
+
+use glam::Vec3;
+use serde::Deserialize;
+
+use crate::{
+    procedural::ProceduralTexture,
+    shader_program::ShaderProgram,
+    texture::{self, resolve_path, ColorSpace, Texture, TextureOptions},
+    texture_data::srgb_to_linear,
+};
+
+/// Converts a color written in sRGB, as the presets and light colors are, into the linear space
+/// lighting happens in while `gamma_correction` is on. Without it the shaders work on the
+/// colors as written.
+pub(crate) fn authored_color(color: Vec3, gamma_correction: bool) -> Vec3 {
+    if gamma_correction {
+        Vec3::from(color.to_array().map(srgb_to_linear))
+    } else {
+        color
+    }
+}
+
+/// Classic material lit by the ambient, diffuse and specular terms of the lights.
+#[derive(Clone, Copy)]
+pub(crate) struct Material {
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    /// Specular exponent divided by 128.
+    pub shininess: f32,
+}
+
+impl Material {
+    pub fn upload(&self, program: &ShaderProgram, gamma_correction: bool) {
+        let color = |color| authored_color(color, gamma_correction).to_array();
+
+        program.set_uniform_vec3("uMaterial.colorAmbient", color(self.ambient));
+        program.set_uniform_vec3("uMaterial.colorDiffuse", color(self.diffuse));
+        program.set_uniform_vec3("uMaterial.colorSpecular", color(self.specular));
+        program.set_uniform_f32("uMaterial.shininess", self.shininess);
+    }
+}
+
+/// Texture maps of the classic [`Material`], used while the textured model is shown.
+pub(crate) struct MaterialMaps {
+    pub diffuse: Option<Texture>,
+    /// Generated texture used instead of `diffuse` while set.
+    pub procedural: Option<(ProceduralTexture, Texture)>,
+    /// Sampling of the generated textures.
+    pub options: TextureOptions,
+    pub specular: Option<Texture>,
+    pub emissive: Option<Texture>,
+    /// Multiplied with the diffuse map.
+    pub tint: Vec3,
+    /// Tangent-space normal map, applied to the PBR material as well.
+    pub normal: Option<Texture>,
+    /// Scales the tilt of the normals from the normal map, `0.0` flattens them.
+    pub normal_strength: f32,
+}
+
+impl MaterialMaps {
+    /// Binds the present maps and tells the program which ones to sample, `enabled` turns them
+    /// all off in favor of the material colors.
+    pub fn upload(&self, program: &ShaderProgram, enabled: bool, gamma_correction: bool) {
+        let diffuse = match &self.procedural {
+            Some((_, texture)) => Some(texture),
+            None => self.diffuse.as_ref(),
+        };
+        let maps = [
+            ("uMaterial.diffuseMap", "uMaterial.useDiffuseMap", diffuse),
+            (
+                "uMaterial.specularMap",
+                "uMaterial.useSpecularMap",
+                self.specular.as_ref(),
+            ),
+            (
+                "uMaterial.emissiveMap",
+                "uMaterial.useEmissiveMap",
+                self.emissive.as_ref(),
+            ),
+        ];
+
+        for (sampler, use_map, map) in maps {
+            let map = map.filter(|_| enabled);
+
+            program.set_uniform_bool(use_map, map.is_some());
+            if let Some(map) = map {
+                texture::bind_sampler(program, sampler, map);
+            }
+        }
+
+        program.set_uniform_vec3(
+            "uMaterial.tint",
+            authored_color(self.tint, gamma_correction).to_array(),
+        );
+    }
+
+    /// Reloads the maps whose files changed, `dt` is the time in seconds since the last call.
+    pub fn poll(&mut self, dt: f32) {
+        let maps = [
+            &mut self.diffuse,
+            &mut self.specular,
+            &mut self.emissive,
+            &mut self.normal,
+        ];
+        for map in maps.into_iter().flatten() {
+            poll_map(map, dt);
+        }
+    }
+
+    /// Generates `kind` and uses it instead of the diffuse map, `None` goes back to the map.
+    pub fn set_procedural(&mut self, gl: &gl::Gl, kind: Option<ProceduralTexture>) {
+        self.procedural = kind.map(|kind| {
+            let texture = Texture::from_image(
+                gl.clone(),
+                &kind.generate(),
+                ColorSpace::Srgb,
+                &self.options,
+            );
+            (kind, texture)
+        });
+    }
+
+    pub fn procedural_kind(&self) -> Option<ProceduralTexture> {
+        self.procedural.as_ref().map(|(kind, _)| *kind)
+    }
+
+    /// Binds the normal map of a program including `lighting.glsl`, `enabled` turns normal
+    /// mapping off.
+    pub fn upload_normal_map(&self, program: &ShaderProgram, enabled: bool) {
+        // The map stays bound while normal mapping is off so the sampler keeps its unit.
+        program.set_uniform_bool("uNormalMapping", enabled && self.normal.is_some());
+        program.set_uniform_f32("uNormalMapStrength", self.normal_strength);
+        if let Some(map) = &self.normal {
+            texture::bind_sampler(program, "uNormalMap", map);
+        }
+    }
+}
+
+impl Default for MaterialMaps {
+    fn default() -> Self {
+        Self {
+            diffuse: None,
+            procedural: None,
+            options: TextureOptions::default(),
+            specular: None,
+            emissive: None,
+            tint: Vec3::ONE,
+            normal: None,
+            normal_strength: 1.0,
+        }
+    }
+}
+
+/// Reloads `map` when its file changed, reporting the error instead of failing.
+fn poll_map(map: &mut Texture, dt: f32) {
+    match map.poll(dt) {
+        Some(Ok(path)) => println!("reloaded {}", path.display()),
+        Some(Err(err)) => eprintln!("{err}, keeping the current image"),
+        None => {}
+    }
+}
+
+/// Opens the texture at `path`, reporting the error instead of failing.
+pub(crate) fn open_map(
+    gl: &gl::Gl,
+    path: &Path,
+    color_space: ColorSpace,
+    options: &TextureOptions,
+) -> Option<Texture> {
+    Texture::open(gl.clone(), path, color_space, options)
+        .map_err(|err| eprintln!("{err}"))
+        .ok()
+}
+
+pub(crate) struct MaterialPreset {
+    pub name: &'static str,
+    pub material: Material,
+}
+
+impl MaterialPreset {
+    /// Index of the preset called `name` in [`MATERIAL_PRESETS`], ignoring case.
+    pub fn find(name: &str) -> Option<usize> {
+        MATERIAL_PRESETS
+            .iter()
+            .position(|preset| preset.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// The classic OpenGL material table.
+pub(crate) const MATERIAL_PRESETS: [MaterialPreset; 24] = [
+    MaterialPreset {
+        name: "emerald",
+        material: Material {
+            ambient: Vec3::new(0.0215, 0.1745, 0.0215),
+            diffuse: Vec3::new(0.07568, 0.61424, 0.07568),
+            specular: Vec3::new(0.633, 0.727811, 0.633),
+            shininess: 0.6,
+        },
+    },
+    MaterialPreset {
+        name: "jade",
+        material: Material {
+            ambient: Vec3::new(0.135, 0.2225, 0.1575),
+            diffuse: Vec3::new(0.54, 0.89, 0.63),
+            specular: Vec3::splat(0.316228),
+            shininess: 0.1,
+        },
+    },
+    MaterialPreset {
+        name: "obsidian",
+        material: Material {
+            ambient: Vec3::new(0.05375, 0.05, 0.06625),
+            diffuse: Vec3::new(0.18275, 0.17, 0.22525),
+            specular: Vec3::new(0.332741, 0.328634, 0.346435),
+            shininess: 0.3,
+        },
+    },
+    MaterialPreset {
+        name: "pearl",
+        material: Material {
+            ambient: Vec3::new(0.25, 0.20725, 0.20725),
+            diffuse: Vec3::new(1.0, 0.829, 0.829),
+            specular: Vec3::splat(0.296648),
+            shininess: 0.088,
+        },
+    },
+    MaterialPreset {
+        name: "ruby",
+        material: Material {
+            ambient: Vec3::new(0.1745, 0.01175, 0.01175),
+            diffuse: Vec3::new(0.61424, 0.04136, 0.04136),
+            specular: Vec3::new(0.727811, 0.626959, 0.626959),
+            shininess: 0.6,
+        },
+    },
+    MaterialPreset {
+        name: "turquoise",
+        material: Material {
+            ambient: Vec3::new(0.1, 0.18725, 0.1745),
+            diffuse: Vec3::new(0.396, 0.74151, 0.69102),
+            specular: Vec3::new(0.297254, 0.30829, 0.306678),
+            shininess: 0.1,
+        },
+    },
+    MaterialPreset {
+        name: "brass",
+        material: Material {
+            ambient: Vec3::new(0.329412, 0.223529, 0.027451),
+            diffuse: Vec3::new(0.780392, 0.568627, 0.113725),
+            specular: Vec3::new(0.992157, 0.941176, 0.807843),
+            shininess: 0.217_948_72,
+        },
+    },
+    MaterialPreset {
+        name: "bronze",
+        material: Material {
+            ambient: Vec3::new(0.2125, 0.1275, 0.054),
+            diffuse: Vec3::new(0.714, 0.4284, 0.18144),
+            specular: Vec3::new(0.393548, 0.271906, 0.166721),
+            shininess: 0.2,
+        },
+    },
+    MaterialPreset {
+        name: "chrome",
+        material: Material {
+            ambient: Vec3::splat(0.25),
+            diffuse: Vec3::splat(0.4),
+            specular: Vec3::splat(0.774597),
+            shininess: 0.6,
+        },
+    },
+    MaterialPreset {
+        name: "copper",
+        material: Material {
+            ambient: Vec3::new(0.19125, 0.0735, 0.0225),
+            diffuse: Vec3::new(0.7038, 0.27048, 0.0828),
+            specular: Vec3::new(0.256777, 0.137622, 0.086014),
+            shininess: 0.1,
+        },
+    },
+    MaterialPreset {
+        name: "gold",
+        material: Material {
+            ambient: Vec3::new(0.24725, 0.1995, 0.0745),
+            diffuse: Vec3::new(0.75164, 0.60648, 0.22648),
+            specular: Vec3::new(0.628281, 0.555802, 0.366065),
+            shininess: 0.4,
+        },
+    },
+    MaterialPreset {
+        name: "silver",
+        material: Material {
+            ambient: Vec3::splat(0.19225),
+            diffuse: Vec3::splat(0.50754),
+            specular: Vec3::splat(0.508273),
+            shininess: 0.4,
+        },
+    },
+    MaterialPreset {
+        name: "black plastic",
+        material: Material {
+            ambient: Vec3::splat(0.0),
+            diffuse: Vec3::splat(0.01),
+            specular: Vec3::splat(0.5),
+            shininess: 0.25,
+        },
+    },
+    MaterialPreset {
+        name: "cyan plastic",
+        material: Material {
+            ambient: Vec3::new(0.0, 0.1, 0.06),
+            diffuse: Vec3::new(0.0, 0.509_803_9, 0.509_803_9),
+            specular: Vec3::splat(0.501_960_8),
+            shininess: 0.25,
+        },
+    },
+    MaterialPreset {
+        name: "green plastic",
+        material: Material {
+            ambient: Vec3::splat(0.0),
+            diffuse: Vec3::new(0.1, 0.35, 0.1),
+            specular: Vec3::new(0.45, 0.55, 0.45),
+            shininess: 0.25,
+        },
+    },
+    MaterialPreset {
+        name: "red plastic",
+        material: Material {
+            ambient: Vec3::splat(0.0),
+            diffuse: Vec3::new(0.5, 0.0, 0.0),
+            specular: Vec3::new(0.7, 0.6, 0.6),
+            shininess: 0.25,
+        },
+    },
+    MaterialPreset {
+        name: "white plastic",
+        material: Material {
+            ambient: Vec3::splat(0.0),
+            diffuse: Vec3::splat(0.55),
+            specular: Vec3::splat(0.7),
+            shininess: 0.25,
+        },
+    },
+    MaterialPreset {
+        name: "yellow plastic",
+        material: Material {
+            ambient: Vec3::splat(0.0),
+            diffuse: Vec3::new(0.5, 0.5, 0.0),
+            specular: Vec3::new(0.6, 0.6, 0.5),
+            shininess: 0.25,
+        },
+    },
+    MaterialPreset {
+        name: "black rubber",
+        material: Material {
+            ambient: Vec3::splat(0.02),
+            diffuse: Vec3::splat(0.01),
+            specular: Vec3::splat(0.4),
+            shininess: 0.078125,
+        },
+    },
+    MaterialPreset {
+        name: "cyan rubber",
+        material: Material {
+            ambient: Vec3::new(0.0, 0.05, 0.05),
+            diffuse: Vec3::new(0.4, 0.5, 0.5),
+            specular: Vec3::new(0.04, 0.7, 0.7),
+            shininess: 0.078125,
+        },
+    },
+    MaterialPreset {
+        name: "green rubber",
+        material: Material {
+            ambient: Vec3::new(0.0, 0.05, 0.0),
+            diffuse: Vec3::new(0.4, 0.5, 0.4),
+            specular: Vec3::new(0.04, 0.7, 0.04),
+            shininess: 0.078125,
+        },
+    },
+    MaterialPreset {
+        name: "red rubber",
+        material: Material {
+            ambient: Vec3::new(0.05, 0.0, 0.0),
+            diffuse: Vec3::new(0.5, 0.4, 0.4),
+            specular: Vec3::new(0.7, 0.04, 0.04),
+            shininess: 0.078125,
+        },
+    },
+    MaterialPreset {
+        name: "white rubber",
+        material: Material {
+            ambient: Vec3::splat(0.05),
+            diffuse: Vec3::splat(0.5),
+            specular: Vec3::splat(0.7),
+            shininess: 0.078125,
+        },
+    },
+    MaterialPreset {
+        name: "yellow rubber",
+        material: Material {
+            ambient: Vec3::new(0.05, 0.05, 0.0),
+            diffuse: Vec3::new(0.5, 0.5, 0.4),
+            specular: Vec3::new(0.7, 0.7, 0.04),
+            shininess: 0.078125,
+        },
+    },
+];
+
+/// Values must match the `ENVIRONMENT_*` defines in `polygon_program/lighting.glsl`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum EnvironmentMode {
+    Off = 0,
+    /// A mirror.
+    Reflect = 1,
+    /// A transparent figure bending the view by [`Environment::ior`].
+    Refract = 2,
+}
+
+impl EnvironmentMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Reflect,
+            Self::Reflect => Self::Refract,
+            Self::Refract => Self::Off,
+        }
+    }
+}
+
+/// The skybox seen in or through the classic material.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Environment {
+    pub mode: EnvironmentMode,
+    /// Index of refraction, also gives the Fresnel reflectance of both modes.
+    pub ior: f32,
+    /// Blends mirrors with the lit material and refraction with reflection by the view angle.
+    pub fresnel: bool,
+}
+
+impl Environment {
+    /// Sets the uniforms of a program including `lighting.glsl`, `available` tells whether
+    /// there is a skybox to show.
+    pub fn upload(&self, program: &ShaderProgram, available: bool) {
+        let mode = if available {
+            self.mode
+        } else {
+            EnvironmentMode::Off
+        };
+
+        program.set_uniform_i32("uEnvironmentMode", mode as i32);
+        program.set_uniform_f32("uRefractiveIndex", self.ior);
+        program.set_uniform_bool("uEnvironmentFresnel", self.fresnel);
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            mode: EnvironmentMode::Off,
+            // Crown glass.
+            ior: 1.52,
+            fresnel: true,
+        }
+    }
+}
+
+/// Metallic-roughness material shaded with a Cook-Torrance BRDF.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct PbrMaterial {
+    /// Base color in linear RGB.
+    pub albedo: Vec3,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Ambient occlusion, `1.0` means the ambient light isn't blocked at all.
+    pub ao: f32,
+}
+
+impl PbrMaterial {
+    /// Roughness values below this make the highlights of point lights vanish.
+    pub const ROUGHNESS_MIN: f32 = 0.05;
+
+    pub fn upload(&self, program: &ShaderProgram) {
+        program.set_uniform_vec3("uPbrMaterial.albedo", self.albedo.to_array());
+        program.set_uniform_f32("uPbrMaterial.metallic", self.metallic);
+        program.set_uniform_f32("uPbrMaterial.roughness", self.roughness);
+        program.set_uniform_f32("uPbrMaterial.ao", self.ao);
+    }
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        Self {
+            albedo: Vec3::new(0.5, 0.02, 0.02),
+            metallic: 0.0,
+            roughness: 0.35,
+            ao: 1.0,
+        }
+    }
+}
+
+/// Texture maps overriding the matching [`PbrMaterial`] channel wherever they are present.
+#[derive(Default)]
+pub(crate) struct PbrMaps {
+    pub albedo: Option<Texture>,
+    pub metallic: Option<Texture>,
+    pub roughness: Option<Texture>,
+    pub ao: Option<Texture>,
+}
+
+impl PbrMaps {
+    /// Loads `albedo.png`, `metallic.png`, `roughness.png` and `ao.png` from `dir`, skipping the
+    /// missing ones. A relative `dir` is resolved like texture paths.
+    pub fn load_dir<P: AsRef<Path>>(gl: &gl::Gl, dir: P) -> Self {
+        let load = |file_name: &str, color_space| {
+            let path = resolve_path(dir.as_ref().join(file_name));
+            path.is_file()
+                .then(|| open_map(gl, &path, color_space, &TextureOptions::default()))
+                .flatten()
+        };
+
+        Self {
+            albedo: load("albedo.png", ColorSpace::Srgb),
+            metallic: load("metallic.png", ColorSpace::Linear),
+            roughness: load("roughness.png", ColorSpace::Linear),
+            ao: load("ao.png", ColorSpace::Linear),
+        }
+    }
+
+    /// Reloads the maps whose files changed, `dt` is the time in seconds since the last call.
+    pub fn poll(&mut self, dt: f32) {
+        let maps = [
+            &mut self.albedo,
+            &mut self.metallic,
+            &mut self.roughness,
+            &mut self.ao,
+        ];
+        for map in maps.into_iter().flatten() {
+            poll_map(map, dt);
+        }
+    }
+
+    /// Binds the present maps and tells the program which channels come from them.
+    pub fn upload(&self, program: &ShaderProgram) {
+        let maps = [
+            (
+                "uPbrMaterial.albedoMap",
+                "uPbrMaterial.useAlbedoMap",
+                &self.albedo,
+            ),
+            (
+                "uPbrMaterial.metallicMap",
+                "uPbrMaterial.useMetallicMap",
+                &self.metallic,
+            ),
+            (
+                "uPbrMaterial.roughnessMap",
+                "uPbrMaterial.useRoughnessMap",
+                &self.roughness,
+            ),
+            ("uPbrMaterial.aoMap", "uPbrMaterial.useAoMap", &self.ao),
+        ];
+
+        for (sampler, use_map, map) in maps {
+            program.set_uniform_bool(use_map, map.is_some());
+            if let Some(map) = map {
+                texture::bind_sampler(program, sampler, map);
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
 #version 330 core
 
+#include "lighting.glsl"
+#include "surface.glsl"
+
 in uint PolarAngleIndex[1];
 in uint AzimuthAngleIndex[1];
 
@@ -7,72 +10,141 @@ layout (points) in;
 layout (triangle_strip, max_vertices = 4) out;
 
 out vec3 Normal;
+out vec3 Tangent;
+out vec3 Bitangent;
 out vec3 FragPos;
 out vec2 TexCoord;
+out vec3 GouraudColor;
+// Position scaled into the unit cube around the figure and the normal, both in object space.
+out vec3 ObjectPos;
+out vec3 ObjectNormal;
 
 uniform uint uSlicesCount;
-uniform float uRadius;
-uniform mat4 uModelMat;
 uniform mat4 uViewMat;
 uniform mat4 uProjectionMat;
 
-vec3 anglesToPos(uint polarAngleIndex, uint azimuthAngleIndex, uint stepsCount);
 vec3 calcNormal(vec3 p1, vec3 p2, vec3 p3);
+vec2 calcTexCoord(uint polarAngleIndex, uint azimuthAngleIndex, vec3 pos);
+void calcTangentFrame(
+  vec3 positions[4],
+  vec2 texCoords[4],
+  out vec3 tangent,
+  out vec3 bitangent
+);
+void emitVertex(
+  vec3 pos,
+  vec3 smoothNormal,
+  vec3 tangent,
+  vec3 bitangent,
+  vec2 texCoord,
+  vec3 faceNormal,
+  vec3 faceCenter
+);
 
 void main() {
-  TexCoord = vec2(
-    float(AzimuthAngleIndex[0]) / float(uSlicesCount),
-    float(PolarAngleIndex[0]) / float(uSlicesCount)
-  );
-
-  vec3 a1 = anglesToPos(PolarAngleIndex[0] + uint(1), AzimuthAngleIndex[0], uSlicesCount);
-  vec4 p1 = uProjectionMat * uViewMat * uModelMat * vec4(a1, 1.0);
+  uint nextPolarAngleIndex = PolarAngleIndex[0] + uint(1);
+  uint nextAzimuthAngleIndex = (AzimuthAngleIndex[0] + uint(1)) % uSlicesCount;
+  // Not wrapped, the last column of quads ends at `u = 1` instead of going back to 0.
+  uint unwrappedAzimuthAngleIndex = AzimuthAngleIndex[0] + uint(1);
 
+  vec3 a1 = anglesToPos(nextPolarAngleIndex, AzimuthAngleIndex[0], uSlicesCount);
   vec3 a2 = anglesToPos(PolarAngleIndex[0], AzimuthAngleIndex[0], uSlicesCount);
-  vec4 p2 = uProjectionMat * uViewMat * uModelMat * vec4(a2, 1.0);
-
-  vec3 a3 = anglesToPos(
-    PolarAngleIndex[0] + uint(1),
-    (AzimuthAngleIndex[0] + uint(1)) % uSlicesCount,
-    uSlicesCount
+  vec3 a3 = anglesToPos(nextPolarAngleIndex, nextAzimuthAngleIndex, uSlicesCount);
+  vec3 a4 = anglesToPos(PolarAngleIndex[0], nextAzimuthAngleIndex, uSlicesCount);
+
+  vec2 texCoords[4] = vec2[4](
+    calcTexCoord(nextPolarAngleIndex, AzimuthAngleIndex[0], a1),
+    calcTexCoord(PolarAngleIndex[0], AzimuthAngleIndex[0], a2),
+    calcTexCoord(nextPolarAngleIndex, unwrappedAzimuthAngleIndex, a3),
+    calcTexCoord(PolarAngleIndex[0], unwrappedAzimuthAngleIndex, a4)
   );
-  vec4 p3 = uProjectionMat * uViewMat * uModelMat * vec4(a3, 1.0);
-
-  vec3 a4 = anglesToPos(PolarAngleIndex[0], (AzimuthAngleIndex[0] + uint(1)) % uSlicesCount, uSlicesCount);
-  vec4 p4 = uProjectionMat * uViewMat * uModelMat * vec4(a4, 1.0);
-
-  Normal = mat3(transpose(inverse(uModelMat))) * calcNormal(a1, a2, a3);
-  FragPos = vec3(uModelMat * vec4((a1.x + a2.x + a3.x + a4.x) / 4.0, (a1.y + a2.y + a3.y + a4.y) / 4.0, (a1.z + a2.z + a3.z + a4.z) / 4.0, 1.0));
 
-  gl_Position = p1;
-  EmitVertex();
-  gl_Position = p2;
-  EmitVertex();
-  gl_Position = p3;
-  EmitVertex();
-  gl_Position = p4;
-  EmitVertex();
+  // Quads on the seam of the spherical and cylindrical projections would otherwise span the
+  // whole texture backwards.
+  float minU = min(min(texCoords[0].x, texCoords[1].x), min(texCoords[2].x, texCoords[3].x));
+  float maxU = max(max(texCoords[0].x, texCoords[1].x), max(texCoords[2].x, texCoords[3].x));
+  for (int i = 0; i < 4; ++i) {
+    if (maxU - minU > 0.5 && texCoords[i].x < 0.5) {
+      texCoords[i].x += 1.0;
+    }
+    texCoords[i] = (uUvTransform * vec3(texCoords[i], 1.0)).xy;
+  }
+
+  vec3 faceNormal = calcNormal(a1, a2, a3);
+  vec3 faceCenter = (a1 + a2 + a3 + a4) / 4.0;
+
+  vec3 tangent;
+  vec3 bitangent;
+  calcTangentFrame(vec3[4](a1, a2, a3, a4), texCoords, tangent, bitangent);
+
+  emitVertex(
+    a1,
+    calcSmoothNormal(nextPolarAngleIndex, AzimuthAngleIndex[0], uSlicesCount),
+    tangent,
+    bitangent,
+    texCoords[0],
+    faceNormal,
+    faceCenter
+  );
+  emitVertex(
+    a2,
+    calcSmoothNormal(PolarAngleIndex[0], AzimuthAngleIndex[0], uSlicesCount),
+    tangent,
+    bitangent,
+    texCoords[1],
+    faceNormal,
+    faceCenter
+  );
+  emitVertex(
+    a3,
+    calcSmoothNormal(nextPolarAngleIndex, nextAzimuthAngleIndex, uSlicesCount),
+    tangent,
+    bitangent,
+    texCoords[2],
+    faceNormal,
+    faceCenter
+  );
+  emitVertex(
+    a4,
+    calcSmoothNormal(PolarAngleIndex[0], nextAzimuthAngleIndex, uSlicesCount),
+    tangent,
+    bitangent,
+    texCoords[3],
+    faceNormal,
+    faceCenter
+  );
   EndPrimitive();
 }
 
-vec3 anglesToPos(uint polarAngleIndex, uint azimuthAngleIndex, uint stepsCount) {
-  #define PI 3.1415926538
-  #define FRAC_PI_2 1.5707963267
-
-  float polarAngleStep = PI / float(stepsCount);
-  float azimuthAngleStep = PI * 2 / float(stepsCount);
-
-  float polarAngle = (float(polarAngleIndex) - float(stepsCount) / 2.0) * polarAngleStep;
-  float azimuthAngle = float(azimuthAngleIndex) * azimuthAngleStep;
-
-  float x = uRadius * cos(polarAngle) * sin(azimuthAngle);
-  float y = uRadius * cos(polarAngle) * cos(azimuthAngle);
-  float z = uRadius * sin(polarAngle) - uRadius
-          * (polarAngle / FRAC_PI_2)
-          * (polarAngle / FRAC_PI_2)
-          * (polarAngle / FRAC_PI_2);
-
-  return vec3(x, z, y);
+void emitVertex(
+  vec3 pos,
+  vec3 smoothNormal,
+  vec3 tangent,
+  vec3 bitangent,
+  vec2 texCoord,
+  vec3 faceNormal,
+  vec3 faceCenter
+) {
+  bool flatShading = uShadingModel == SHADING_FLAT;
+  vec3 objectNormal = normalize(flatShading ? faceNormal : smoothNormal);
+
+  Normal = mat3(transpose(inverse(uModelMat))) * objectNormal;
+  Tangent = mat3(uModelMat) * tangent;
+  Bitangent = mat3(uModelMat) * bitangent;
+  FragPos = vec3(uModelMat * vec4(flatShading ? faceCenter : pos, 1.0));
+  TexCoord = texCoord;
+  ObjectPos = pos / (2.0 * uRadius) + 0.5;
+  ObjectNormal = objectNormal;
+
+  if (uShadingModel == SHADING_GOURAUD) {
+    setTriplanarPoint(ObjectPos, ObjectNormal);
+    GouraudColor = calcLighting(normalize(Normal), FragPos, TexCoord);
+  } else {
+    GouraudColor = vec3(0.0);
+  }
+
+  gl_Position = uProjectionMat * uViewMat * uModelMat * vec4(pos, 1.0);
+  EmitVertex();
 }
 
 vec3 calcNormal(vec3 p1, vec3 p2, vec3 p3) {
@@ -81,3 +153,48 @@ vec3 calcNormal(vec3 p1, vec3 p2, vec3 p3) {
 
   return vec3(u.y * v.z - u.z * v.y, u.z * v.x - u.x * v.z, u.x * v.y - u.y * v.x);
 }
+
+// Untransformed texture coordinates of the vertex at `pos` with the given grid indices.
+vec2 calcTexCoord(uint polarAngleIndex, uint azimuthAngleIndex, vec3 pos) {
+  vec3 boxPos = pos / (2.0 * uRadius) + 0.5;
+  vec3 direction = normalize(pos);
+
+  switch (uUvProjection) {
+    case PROJECTION_SPHERICAL:
+      return vec2(0.5 + atan(direction.x, direction.z) / (2.0 * PI), 0.5 - asin(direction.y) / PI);
+    case PROJECTION_CYLINDRICAL:
+      return vec2(0.5 + atan(pos.x, pos.z) / (2.0 * PI), 1.0 - boxPos.y);
+    case PROJECTION_PLANAR:
+      return planarTexCoord(boxPos, uUvPlanarAxis);
+    default:
+      return vec2(float(azimuthAngleIndex), float(polarAngleIndex)) / float(uSlicesCount);
+  }
+}
+
+// Directions in object space in which the `u` and `v` texture coordinates grow across the quad,
+// solved from its edges and their texture coordinate deltas. They follow the projection and the
+// UV transform, a mirrored texture gets a bitangent against the usual handedness. Falls back to
+// the edges themselves where the projection squashes the quad into a line.
+void calcTangentFrame(
+  vec3 positions[4],
+  vec2 texCoords[4],
+  out vec3 tangent,
+  out vec3 bitangent
+) {
+  // Averaged over opposite edges so quads touching the poles, where an edge collapses, work too.
+  vec3 edgeU = positions[2] + positions[3] - positions[0] - positions[1];
+  vec3 edgeV = positions[0] + positions[2] - positions[1] - positions[3];
+  vec2 deltaU = texCoords[2] + texCoords[3] - texCoords[0] - texCoords[1];
+  vec2 deltaV = texCoords[0] + texCoords[2] - texCoords[1] - texCoords[3];
+
+  float det = deltaU.x * deltaV.y - deltaV.x * deltaU.y;
+  if (abs(det) <= 1e-4 * length(deltaU) * length(deltaV)) {
+    tangent = normalize(edgeU);
+    bitangent = normalize(edgeV);
+    return;
+  }
+
+  // Only the directions matter, the sign of the determinant keeps them from flipping.
+  tangent = normalize(sign(det) * (edgeU * deltaV.y - edgeV * deltaU.y));
+  bitangent = normalize(sign(det) * (edgeV * deltaU.x - edgeU * deltaV.x));
+}
@@ -0,0 +1,483 @@
+// Shared by the geometry shader for Gouraud shading and the fragment shader for the rest.
+
+#define MAX_DIR_LIGHTS 4
+#define MAX_POINT_LIGHTS 8
+#define MAX_SPOT_LIGHTS 4
+
+#define SHADING_FLAT 0
+#define SHADING_GOURAUD 1
+#define SHADING_PHONG 2
+#define SHADING_BLINN_PHONG 3
+
+#define PROJECTION_PARAMETRIC 0
+#define PROJECTION_SPHERICAL 1
+#define PROJECTION_CYLINDRICAL 2
+#define PROJECTION_PLANAR 3
+#define PROJECTION_TRIPLANAR 4
+
+#define ENVIRONMENT_OFF 0
+#define ENVIRONMENT_REFLECT 1
+#define ENVIRONMENT_REFRACT 2
+
+// `intensity` is only used in PBR mode: irradiance in W/m² for directional lights and radiant
+// intensity in W/sr for the others.
+struct DirectionalLight {
+  vec3 direction;
+  float intensity;
+
+  vec3 ambient;
+  vec3 diffuse;
+  vec3 specular;
+};
+
+struct PointLight {
+  vec3 position;
+  float intensity;
+
+  float constant;
+  float linear;
+  float quadratic;
+
+  vec3 ambient;
+  vec3 diffuse;
+  vec3 specular;
+};
+
+struct SpotLight {
+  vec3 position;
+  vec3 direction;
+  float intensity;
+  // Cosines of the cone half-angles.
+  float innerCutOff;
+  float outerCutOff;
+
+  float constant;
+  float linear;
+  float quadratic;
+
+  vec3 ambient;
+  vec3 diffuse;
+  vec3 specular;
+};
+
+// The diffuse map replaces both the ambient and the diffuse color and is multiplied by `tint`,
+// the specular map scales the specular color. The emissive map is added on top of the lighting.
+struct Material {
+  sampler2D diffuseMap;
+  sampler2D specularMap;
+  sampler2D emissiveMap;
+
+  bool useDiffuseMap;
+  bool useSpecularMap;
+  bool useEmissiveMap;
+
+  vec3 colorAmbient;
+  vec3 colorDiffuse;
+  vec3 colorSpecular;
+  vec3 tint;
+
+  float shininess;
+};
+
+uniform DirectionalLight uDirLights[MAX_DIR_LIGHTS];
+uniform int uDirLightsCount;
+uniform PointLight uPointLights[MAX_POINT_LIGHTS];
+uniform int uPointLightsCount;
+uniform SpotLight uSpotLights[MAX_SPOT_LIGHTS];
+uniform int uSpotLightsCount;
+uniform vec3 uViewPos;
+
+uniform Material uMaterial;
+uniform int uShadingModel;
+
+// Tangent-space normal map shared by the classic and PBR materials, `uNormalMapStrength` scales
+// the tilt of the sampled normals.
+uniform bool uNormalMapping;
+uniform sampler2D uNormalMap;
+uniform float uNormalMapStrength;
+
+// How texture coordinates are derived from the surface. All projections but the triplanar one
+// are computed per vertex by the geometry shader, triplanar projection blends three planar
+// samples in `sampleMap`. `uUvTransform` scales, rotates and offsets the result.
+uniform int uUvProjection;
+uniform int uUvPlanarAxis;
+uniform mat3 uUvTransform;
+
+// Set by `setTriplanarPoint` before the lighting of every vertex or fragment.
+vec3 triplanarPos;
+vec3 triplanarWeights;
+
+// The triplanar normal map builds its frames from the object axes.
+uniform mat4 uModelMat;
+
+// The skybox seen in or through the figure, see `applyEnvironment`.
+uniform int uEnvironmentMode;
+uniform samplerCube uEnvironmentMap;
+uniform float uRefractiveIndex;
+uniform bool uEnvironmentFresnel;
+
+// Shadows are cast by the first enabled directional light and the first enabled point light.
+uniform bool uShadowsEnabled;
+uniform float uShadowBias;
+uniform sampler2D uDirShadowMap;
+uniform mat4 uDirLightSpaceMat;
+uniform samplerCube uPointShadowMap;
+uniform float uPointShadowFarPlane;
+
+vec3 calcDirectionalLight(
+  Material material,
+  vec2 texCoord,
+  DirectionalLight light,
+  vec3 normal,
+  vec3 viewDir,
+  float shadow
+);
+
+vec3 calcPointLight(
+  Material material,
+  vec2 texCoord,
+  PointLight light,
+  vec3 normal,
+  vec3 fragPos,
+  vec3 viewDir,
+  float shadow
+);
+
+vec3 calcSpotLight(
+  Material material,
+  vec2 texCoord,
+  SpotLight light,
+  vec3 normal,
+  vec3 fragPos,
+  vec3 viewDir
+);
+
+float calcSpecular(vec3 lightDirection, vec3 normal, vec3 viewDir, float shininess);
+vec3 applyNormalMap(vec3 normal, vec3 tangent, vec3 bitangent, vec2 texCoord);
+vec3 perturbNormal(vec3 normal, vec3 tangent, vec3 bitangent, vec3 texel);
+vec3 materialAmbient(Material material, vec2 texCoord);
+vec3 materialDiffuse(Material material, vec2 texCoord);
+vec3 materialSpecular(Material material, vec2 texCoord);
+float calcDirShadow(vec3 fragPos, vec3 normal, vec3 lightDirection);
+float calcPointShadow(vec3 fragPos, vec3 normal, vec3 lightPosition);
+vec4 sampleMap(sampler2D map, vec2 texCoord);
+vec2 planarTexCoord(vec3 boxPos, int axis);
+vec3 applyEnvironment(vec3 color, vec3 normal, vec3 fragPos);
+
+vec3 calcLighting(vec3 normal, vec3 fragPos, vec2 texCoord) {
+  vec3 viewDir = normalize(uViewPos - fragPos);
+
+  vec3 result = vec3(0.0);
+
+  for (int i = 0; i < uDirLightsCount; ++i) {
+    float shadow = uShadowsEnabled && i == 0
+      ? calcDirShadow(fragPos, normal, normalize(-uDirLights[i].direction))
+      : 0.0;
+    result += calcDirectionalLight(uMaterial, texCoord, uDirLights[i], normal, viewDir, shadow);
+  }
+
+  for (int i = 0; i < uPointLightsCount; ++i) {
+    float shadow = uShadowsEnabled && i == 0
+      ? calcPointShadow(fragPos, normal, uPointLights[i].position)
+      : 0.0;
+    result += calcPointLight(
+      uMaterial,
+      texCoord,
+      uPointLights[i],
+      normal,
+      fragPos,
+      viewDir,
+      shadow
+    );
+  }
+
+  for (int i = 0; i < uSpotLightsCount; ++i) {
+    result += calcSpotLight(uMaterial, texCoord, uSpotLights[i], normal, fragPos, viewDir);
+  }
+
+  if (uMaterial.useEmissiveMap) {
+    result += sampleMap(uMaterial.emissiveMap, texCoord).rgb;
+  }
+
+  return result;
+}
+
+// Unit normal perturbed by the normal map, or `normal` itself when normal mapping is off.
+// `tangent` and `bitangent` point where `texCoord` grows along `u` and `v`. The triplanar
+// projection ignores them and gives each of its three samples the frame of its own axis.
+vec3 applyNormalMap(vec3 normal, vec3 tangent, vec3 bitangent, vec2 texCoord) {
+  if (!uNormalMapping) {
+    return normal;
+  }
+
+  if (uUvProjection != PROJECTION_TRIPLANAR) {
+    return perturbNormal(normal, tangent, bitangent, texture(uNormalMap, texCoord).rgb);
+  }
+
+  // Object space directions of `u` and `v` in `planarTexCoord` along each axis.
+  const vec3 planarTangents[3] = vec3[](vec3(0, 0, 1), vec3(1, 0, 0), vec3(1, 0, 0));
+  const vec3 planarBitangents[3] = vec3[](vec3(0, -1, 0), vec3(0, 0, 1), vec3(0, -1, 0));
+  // Transforming the coordinates transforms their directions by the inverse.
+  mat2 inverseUvTransform = inverse(mat2(uUvTransform));
+
+  vec3 result = vec3(0.0);
+  for (int axis = 0; axis < 3; ++axis) {
+    vec2 planar = (uUvTransform * vec3(planarTexCoord(triplanarPos, axis), 1.0)).xy;
+    mat2x3 frame = mat2x3(planarTangents[axis], planarBitangents[axis]) * inverseUvTransform;
+    vec3 texel = texture(uNormalMap, planar).rgb;
+
+    result += triplanarWeights[axis] * perturbNormal(
+      normal,
+      mat3(uModelMat) * frame[0],
+      mat3(uModelMat) * frame[1],
+      texel
+    );
+  }
+
+  return normalize(result);
+}
+
+// `normal` tilted by a normal map `texel` in the frame of `tangent` and `bitangent`, neither has
+// to be unit or perpendicular to the normal. Only the side the bitangent is on is kept, so the
+// frame stays orthonormal while mirrored textures are still tilted the right way.
+vec3 perturbNormal(vec3 normal, vec3 tangent, vec3 bitangent, vec3 texel) {
+  // Gram-Schmidt, the interpolated tangent is no longer exactly perpendicular to the normal.
+  tangent -= dot(tangent, normal) * normal;
+  // Along the normal the frame is undefined, it only happens where a triplanar weight is 0.
+  if (dot(tangent, tangent) < 1e-8) {
+    return normal;
+  }
+  tangent = normalize(tangent);
+  vec3 crossed = cross(normal, tangent);
+  bitangent = dot(crossed, bitangent) < 0.0 ? -crossed : crossed;
+
+  vec3 mapped = texel * 2.0 - 1.0;
+  mapped.xy *= uNormalMapStrength;
+
+  return normalize(mat3(tangent, bitangent, normal) * mapped);
+}
+
+vec3 materialAmbient(Material material, vec2 texCoord) {
+  return material.useDiffuseMap
+    ? sampleMap(material.diffuseMap, texCoord).rgb * material.tint
+    : material.colorAmbient;
+}
+
+vec3 materialDiffuse(Material material, vec2 texCoord) {
+  return material.useDiffuseMap
+    ? sampleMap(material.diffuseMap, texCoord).rgb * material.tint
+    : material.colorDiffuse;
+}
+
+vec3 materialSpecular(Material material, vec2 texCoord) {
+  return material.useSpecularMap
+    ? sampleMap(material.specularMap, texCoord).rgb * material.colorSpecular
+    : material.colorSpecular;
+}
+
+float calcSpecular(vec3 lightDirection, vec3 normal, vec3 viewDir, float shininess) {
+  if (uShadingModel == SHADING_BLINN_PHONG) {
+    vec3 halfwayDirection = normalize(lightDirection + viewDir);
+    return pow(max(dot(normal, halfwayDirection), 0.0), shininess * 128.0);
+  }
+
+  vec3 reflectDirection = reflect(-lightDirection, normal);
+  return pow(max(dot(viewDir, reflectDirection), 0.0), shininess * 128.0);
+}
+
+// Fraction of the light blocked at `fragPos`, 3x3 PCF over the directional shadow map.
+float calcDirShadow(vec3 fragPos, vec3 normal, vec3 lightDirection) {
+  vec4 lightSpacePos = uDirLightSpaceMat * vec4(fragPos, 1.0);
+  vec3 projected = lightSpacePos.xyz / lightSpacePos.w * 0.5 + 0.5;
+
+  if (projected.z > 1.0) {
+    return 0.0;
+  }
+
+  float bias = max(uShadowBias * (1.0 - dot(normal, lightDirection)), uShadowBias * 0.1);
+  vec2 texelSize = 1.0 / vec2(textureSize(uDirShadowMap, 0));
+
+  float shadow = 0.0;
+  for (int x = -1; x <= 1; ++x) {
+    for (int y = -1; y <= 1; ++y) {
+      float closestDepth = texture(uDirShadowMap, projected.xy + vec2(x, y) * texelSize).r;
+      shadow += projected.z - bias > closestDepth ? 1.0 : 0.0;
+    }
+  }
+
+  return shadow / 9.0;
+}
+
+// Fraction of the light blocked at `fragPos`, PCF over a few directions around the sample in
+// the point light cube shadow map, which stores distances divided by the far plane.
+float calcPointShadow(vec3 fragPos, vec3 normal, vec3 lightPosition) {
+  const vec3 offsets[20] = vec3[](
+    vec3(1, 1, 1), vec3(1, -1, 1), vec3(-1, -1, 1), vec3(-1, 1, 1),
+    vec3(1, 1, -1), vec3(1, -1, -1), vec3(-1, -1, -1), vec3(-1, 1, -1),
+    vec3(1, 1, 0), vec3(1, -1, 0), vec3(-1, -1, 0), vec3(-1, 1, 0),
+    vec3(1, 0, 1), vec3(-1, 0, 1), vec3(1, 0, -1), vec3(-1, 0, -1),
+    vec3(0, 1, 1), vec3(0, -1, 1), vec3(0, -1, -1), vec3(0, 1, -1)
+  );
+
+  vec3 fragToLight = fragPos - lightPosition;
+  float currentDepth = length(fragToLight) / uPointShadowFarPlane;
+
+  if (currentDepth > 1.0) {
+    return 0.0;
+  }
+
+  float bias = max(
+    uShadowBias * (1.0 - dot(normal, normalize(-fragToLight))),
+    uShadowBias * 0.1
+  );
+  float diskRadius = 0.002 + 0.01 * currentDepth;
+
+  float shadow = 0.0;
+  for (int i = 0; i < 20; ++i) {
+    vec3 sampleDirection = fragToLight + offsets[i] * diskRadius * length(fragToLight);
+    float closestDepth = texture(uPointShadowMap, sampleDirection).r;
+    shadow += currentDepth - bias > closestDepth ? 1.0 : 0.0;
+  }
+
+  return shadow / 20.0;
+}
+
+vec3 calcDirectionalLight(
+  Material material,
+  vec2 texCoord,
+  DirectionalLight light,
+  vec3 normal,
+  vec3 viewDir,
+  float shadow
+) {
+  vec3 lightDirection = normalize(-light.direction);
+
+  float diff = max(dot(normal, lightDirection), 0.0);
+
+  float spec = calcSpecular(lightDirection, normal, viewDir, material.shininess);
+
+  vec3 ambient = light.ambient * materialAmbient(material, texCoord);
+  vec3 diffuse = light.diffuse * diff * materialDiffuse(material, texCoord);
+  vec3 specular = light.specular * spec * materialSpecular(material, texCoord);
+
+  return ambient + (1.0 - shadow) * (diffuse + specular);
+}
+
+vec3 calcPointLight(
+  Material material,
+  vec2 texCoord,
+  PointLight light,
+  vec3 normal,
+  vec3 fragPos,
+  vec3 viewDir,
+  float shadow
+) {
+  vec3 lightDirection = normalize(light.position - fragPos);
+
+  float diff = max(dot(normal, lightDirection), 0.0);
+
+  float spec = calcSpecular(lightDirection, normal, viewDir, material.shininess);
+
+  float distance = length(light.position - fragPos);
+  float attenuation = 1.0 /
+    (light.constant + light.linear * distance + light.quadratic * distance * distance);
+
+  vec3 ambient = attenuation * light.ambient * materialAmbient(material, texCoord);
+  vec3 diffuse = attenuation * light.diffuse * diff * materialDiffuse(material, texCoord);
+  vec3 specular = attenuation * light.specular * spec * materialSpecular(material, texCoord);
+
+  return ambient + (1.0 - shadow) * (diffuse + specular);
+}
+
+vec3 calcSpotLight(
+  Material material,
+  vec2 texCoord,
+  SpotLight light,
+  vec3 normal,
+  vec3 fragPos,
+  vec3 viewDir
+) {
+  vec3 lightDirection = normalize(light.position - fragPos);
+
+  float diff = max(dot(normal, lightDirection), 0.0);
+
+  float spec = calcSpecular(lightDirection, normal, viewDir, material.shininess);
+
+  float distance = length(light.position - fragPos);
+  float attenuation = 1.0 /
+    (light.constant + light.linear * distance + light.quadratic * distance * distance);
+
+  float theta = dot(lightDirection, normalize(-light.direction));
+  // Cones closer than the precision of their cosines would divide by zero.
+  float epsilon = max(light.innerCutOff - light.outerCutOff, 1e-4);
+  float intensity = clamp((theta - light.outerCutOff) / epsilon, 0.0, 1.0);
+
+  vec3 ambient = attenuation * light.ambient * materialAmbient(material, texCoord);
+  vec3 diffuse = attenuation * intensity * light.diffuse * diff
+    * materialDiffuse(material, texCoord);
+  vec3 specular = attenuation * intensity * light.specular * spec
+    * materialSpecular(material, texCoord);
+
+  return (ambient + diffuse + specular);
+}
+
+// Replaces the lit `color` by the reflected or refracted skybox. With Fresnel on, a mirror blends
+// the reflection with `color` and a refractive figure blends the refraction with the reflection,
+// both by the Schlick approximation for `uRefractiveIndex`.
+vec3 applyEnvironment(vec3 color, vec3 normal, vec3 fragPos) {
+  if (uEnvironmentMode == ENVIRONMENT_OFF) {
+    return color;
+  }
+
+  vec3 incident = normalize(fragPos - uViewPos);
+  vec3 reflection = texture(uEnvironmentMap, reflect(incident, normal)).rgb;
+
+  float f0 = pow((uRefractiveIndex - 1.0) / (uRefractiveIndex + 1.0), 2.0);
+  float fresnel = f0 + (1.0 - f0) * pow(1.0 - max(dot(-incident, normal), 0.0), 5.0);
+
+  if (uEnvironmentMode == ENVIRONMENT_REFLECT) {
+    return uEnvironmentFresnel ? mix(color, reflection, fresnel) : reflection;
+  }
+
+  // A zero vector means total internal reflection.
+  vec3 refracted = refract(incident, normal, 1.0 / uRefractiveIndex);
+  vec3 refraction = refracted == vec3(0.0)
+    ? reflection
+    : texture(uEnvironmentMap, refracted).rgb;
+
+  return uEnvironmentFresnel ? mix(refraction, reflection, fresnel) : refraction;
+}
+
+// Untransformed coordinates of a planar projection along `axis`, `boxPos` is the position
+// scaled into the unit cube around the figure. The image is upright on the side views.
+vec2 planarTexCoord(vec3 boxPos, int axis) {
+  if (axis == 0) {
+    return vec2(boxPos.z, 1.0 - boxPos.y);
+  }
+  if (axis == 1) {
+    return vec2(boxPos.x, boxPos.z);
+  }
+  return vec2(boxPos.x, 1.0 - boxPos.y);
+}
+
+// `boxPos` as in `planarTexCoord`, `normal` is in object space.
+void setTriplanarPoint(vec3 boxPos, vec3 normal) {
+  triplanarPos = boxPos;
+  // Sharpened so the blend between two projections stays narrow.
+  vec3 weights = pow(abs(normal), vec3(4.0));
+  triplanarWeights = weights / (weights.x + weights.y + weights.z);
+}
+
+vec4 sampleMap(sampler2D map, vec2 texCoord) {
+  if (uUvProjection != PROJECTION_TRIPLANAR) {
+    return texture(map, texCoord);
+  }
+
+  vec4 result = vec4(0.0);
+  for (int axis = 0; axis < 3; ++axis) {
+    vec2 planar = (uUvTransform * vec3(planarTexCoord(triplanarPos, axis), 1.0)).xy;
+    result += texture(map, planar) * triplanarWeights[axis];
+  }
+
+  return result;
+}
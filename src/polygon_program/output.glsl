@@ -0,0 +1,17 @@
+// Shared by the fragment shaders writing to the default framebuffer. Color maps are decoded from
+// sRGB by the sampler, lighting happens in linear space and `encodeOutput` converts the result
+// back to sRGB unless `uGammaCorrection` is off.
+
+uniform bool uGammaCorrection;
+
+vec3 encodeOutput(vec3 color) {
+  if (!uGammaCorrection) {
+    return color;
+  }
+
+  // The exact sRGB transfer function, the inverse of what sRGB textures are sampled with.
+  color = clamp(color, 0.0, 1.0);
+  vec3 curve = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+
+  return mix(color * 12.92, curve, step(0.0031308, color));
+}
@@ -0,0 +1,173 @@
+#version 330 core
+
+#include "lighting.glsl"
+#include "output.glsl"
+
+#define PI 3.1415926538
+
+in vec3 Normal;
+in vec3 Tangent;
+in vec3 Bitangent;
+in vec3 FragPos;
+in vec2 TexCoord;
+in vec3 ObjectPos;
+in vec3 ObjectNormal;
+
+out vec4 FragColor;
+
+// Metallic-roughness material, each channel can be replaced by a texture map. Scalar maps are
+// read from their red channel.
+struct PbrMaterial {
+  vec3 albedo;
+  float metallic;
+  float roughness;
+  float ao;
+
+  sampler2D albedoMap;
+  sampler2D metallicMap;
+  sampler2D roughnessMap;
+  sampler2D aoMap;
+
+  bool useAlbedoMap;
+  bool useMetallicMap;
+  bool useRoughnessMap;
+  bool useAoMap;
+};
+
+uniform PbrMaterial uPbrMaterial;
+uniform vec3 uPbrAmbient;
+
+float distributionGGX(vec3 normal, vec3 halfway, float roughness);
+float geometrySmith(vec3 normal, vec3 viewDir, vec3 lightDirection, float roughness);
+vec3 fresnelSchlick(float cosTheta, vec3 f0);
+vec3 lightColor(vec3 diffuse);
+
+// Outgoing radiance towards the viewer for light arriving from `lightDirection` with `radiance`.
+vec3 cookTorrance(
+  vec3 radiance,
+  vec3 lightDirection,
+  vec3 normal,
+  vec3 viewDir,
+  vec3 albedo,
+  float metallic,
+  float roughness
+) {
+  vec3 halfway = normalize(viewDir + lightDirection);
+  vec3 f0 = mix(vec3(0.04), albedo, metallic);
+
+  float ndf = distributionGGX(normal, halfway, roughness);
+  float geometry = geometrySmith(normal, viewDir, lightDirection, roughness);
+  vec3 fresnel = fresnelSchlick(max(dot(halfway, viewDir), 0.0), f0);
+
+  float nDotL = max(dot(normal, lightDirection), 0.0);
+  float nDotV = max(dot(normal, viewDir), 0.0);
+  vec3 specular = ndf * geometry * fresnel / (4.0 * nDotV * nDotL + 0.0001);
+
+  vec3 diffuseShare = (vec3(1.0) - fresnel) * (1.0 - metallic);
+
+  return (diffuseShare * albedo / PI + specular) * radiance * nDotL;
+}
+
+void main() {
+  setTriplanarPoint(ObjectPos, normalize(ObjectNormal));
+  vec3 normal = applyNormalMap(normalize(Normal), Tangent, Bitangent, TexCoord);
+  vec3 viewDir = normalize(uViewPos - FragPos);
+
+  vec3 albedo = uPbrMaterial.useAlbedoMap
+    ? sampleMap(uPbrMaterial.albedoMap, TexCoord).rgb
+    : uPbrMaterial.albedo;
+  float metallic = uPbrMaterial.useMetallicMap
+    ? sampleMap(uPbrMaterial.metallicMap, TexCoord).r
+    : uPbrMaterial.metallic;
+  float roughness = uPbrMaterial.useRoughnessMap
+    ? sampleMap(uPbrMaterial.roughnessMap, TexCoord).r
+    : uPbrMaterial.roughness;
+  float ao = uPbrMaterial.useAoMap
+    ? sampleMap(uPbrMaterial.aoMap, TexCoord).r
+    : uPbrMaterial.ao;
+
+  vec3 result = vec3(0.0);
+
+  for (int i = 0; i < uDirLightsCount; ++i) {
+    vec3 lightDirection = normalize(-uDirLights[i].direction);
+    float shadow = uShadowsEnabled && i == 0
+      ? calcDirShadow(FragPos, normal, lightDirection)
+      : 0.0;
+    vec3 radiance = lightColor(uDirLights[i].diffuse) * uDirLights[i].intensity;
+
+    result += (1.0 - shadow)
+      * cookTorrance(radiance, lightDirection, normal, viewDir, albedo, metallic, roughness);
+  }
+
+  for (int i = 0; i < uPointLightsCount; ++i) {
+    vec3 toLight = uPointLights[i].position - FragPos;
+    float shadow = uShadowsEnabled && i == 0
+      ? calcPointShadow(FragPos, normal, uPointLights[i].position)
+      : 0.0;
+    vec3 radiance = lightColor(uPointLights[i].diffuse) * uPointLights[i].intensity
+      / dot(toLight, toLight);
+
+    result += (1.0 - shadow) * cookTorrance(
+      radiance,
+      normalize(toLight),
+      normal,
+      viewDir,
+      albedo,
+      metallic,
+      roughness
+    );
+  }
+
+  for (int i = 0; i < uSpotLightsCount; ++i) {
+    vec3 toLight = uSpotLights[i].position - FragPos;
+    vec3 lightDirection = normalize(toLight);
+
+    float theta = dot(lightDirection, normalize(-uSpotLights[i].direction));
+    float epsilon = max(uSpotLights[i].innerCutOff - uSpotLights[i].outerCutOff, 1e-4);
+    float cone = clamp((theta - uSpotLights[i].outerCutOff) / epsilon, 0.0, 1.0);
+
+    vec3 radiance = lightColor(uSpotLights[i].diffuse) * uSpotLights[i].intensity * cone
+      / dot(toLight, toLight);
+
+    result += cookTorrance(radiance, lightDirection, normal, viewDir, albedo, metallic, roughness);
+  }
+
+  result += uPbrAmbient * albedo * ao;
+
+  // Reinhard tone mapping.
+  result = result / (result + vec3(1.0));
+
+  FragColor = vec4(encodeOutput(result), 1.0);
+}
+
+float distributionGGX(vec3 normal, vec3 halfway, float roughness) {
+  float a = roughness * roughness;
+  float a2 = a * a;
+  float nDotH = max(dot(normal, halfway), 0.0);
+  float denominator = nDotH * nDotH * (a2 - 1.0) + 1.0;
+
+  return a2 / (PI * denominator * denominator);
+}
+
+float geometrySchlickGGX(float nDotV, float roughness) {
+  float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+
+  return nDotV / (nDotV * (1.0 - k) + k);
+}
+
+float geometrySmith(vec3 normal, vec3 viewDir, vec3 lightDirection, float roughness) {
+  return geometrySchlickGGX(max(dot(normal, viewDir), 0.0), roughness)
+    * geometrySchlickGGX(max(dot(normal, lightDirection), 0.0), roughness);
+}
+
+vec3 fresnelSchlick(float cosTheta, vec3 f0) {
+  return f0 + (1.0 - f0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+}
+
+// The diffuse color of a light only gives its hue in PBR mode, the power comes from
+// `intensity`.
+vec3 lightColor(vec3 diffuse) {
+  float maxComponent = max(diffuse.r, max(diffuse.g, diffuse.b));
+
+  return maxComponent > 0.0 ? diffuse / maxComponent : vec3(0.0);
+}
@@ -0,0 +1,49 @@
+// Parametric surface of the figure shared by the polygon and shadow geometry shaders.
+
+uniform float uRadius;
+
+#define PI 3.1415926538
+#define FRAC_PI_2 1.5707963267
+
+vec2 indicesToAngles(uint polarAngleIndex, uint azimuthAngleIndex, uint stepsCount) {
+  float polarAngleStep = PI / float(stepsCount);
+  float azimuthAngleStep = PI * 2 / float(stepsCount);
+
+  float polarAngle = (float(polarAngleIndex) - float(stepsCount) / 2.0) * polarAngleStep;
+  float azimuthAngle = float(azimuthAngleIndex) * azimuthAngleStep;
+
+  return vec2(polarAngle, azimuthAngle);
+}
+
+vec3 anglesToPos(uint polarAngleIndex, uint azimuthAngleIndex, uint stepsCount) {
+  vec2 angles = indicesToAngles(polarAngleIndex, azimuthAngleIndex, stepsCount);
+  float polarAngle = angles.x;
+  float azimuthAngle = angles.y;
+
+  float x = uRadius * cos(polarAngle) * sin(azimuthAngle);
+  float y = uRadius * cos(polarAngle) * cos(azimuthAngle);
+  float z = uRadius * sin(polarAngle) - uRadius
+          * (polarAngle / FRAC_PI_2)
+          * (polarAngle / FRAC_PI_2)
+          * (polarAngle / FRAC_PI_2);
+
+  return vec3(x, z, y);
+}
+
+// Normal from the analytic partial derivatives of `anglesToPos`. The azimuthal derivative is
+// divided by cos(polarAngle) so it doesn't vanish at the poles.
+vec3 calcSmoothNormal(uint polarAngleIndex, uint azimuthAngleIndex, uint stepsCount) {
+  vec2 angles = indicesToAngles(polarAngleIndex, azimuthAngleIndex, stepsCount);
+  float polarAngle = angles.x;
+  float azimuthAngle = angles.y;
+
+  vec3 dPolar = vec3(
+    -uRadius * sin(polarAngle) * sin(azimuthAngle),
+    uRadius * cos(polarAngle)
+      - 3.0 * uRadius * polarAngle * polarAngle / (FRAC_PI_2 * FRAC_PI_2 * FRAC_PI_2),
+    -uRadius * sin(polarAngle) * cos(azimuthAngle)
+  );
+  vec3 dAzimuth = vec3(cos(azimuthAngle), 0.0, -sin(azimuthAngle));
+
+  return normalize(cross(dAzimuth, dPolar));
+}
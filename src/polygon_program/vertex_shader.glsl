@@ -1,7 +1,8 @@
 #version 330 core
 
-in uint iPolarAngleIndex;
-in uint iAzimuthAngleIndex;
+// Fixed locations let the classic and PBR programs share one vertex array.
+layout (location = 0) in uint iPolarAngleIndex;
+layout (location = 1) in uint iAzimuthAngleIndex;
 
 out uint PolarAngleIndex;
 out uint AzimuthAngleIndex;
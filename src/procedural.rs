@@ -0,0 +1,252 @@
+use glam::{Vec2, Vec3};
+use image::{DynamicImage, Rgb, RgbImage};
+use serde::Deserialize;
+
+/// Textures generated on the CPU, usable anywhere an image file is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ProceduralTexture {
+    Checkerboard,
+    /// Grid of numbered cells, shows how the texture is laid out on the figure.
+    UvGrid,
+    /// Red grows with `u` and green with `v`, shows how the texture is stretched.
+    UvGradient,
+    Noise,
+    Marble,
+}
+
+impl ProceduralTexture {
+    pub const ALL: [Self; 5] = [
+        Self::Checkerboard,
+        Self::UvGrid,
+        Self::UvGradient,
+        Self::Noise,
+        Self::Marble,
+    ];
+
+    /// Side of the generated images in pixels.
+    const SIZE: u32 = 1024;
+
+    pub fn generate(self) -> DynamicImage {
+        match self {
+            Self::Checkerboard => checkerboard(Self::SIZE, 16),
+            Self::UvGrid => uv_grid(Self::SIZE, 8),
+            Self::UvGradient => uv_gradient(Self::SIZE),
+            Self::Noise => noise(Self::SIZE),
+            Self::Marble => marble(Self::SIZE),
+        }
+    }
+}
+
+/// Gray and white checkerboard, the fallback for textures that failed to load.
+pub(crate) fn checkerboard(size: u32, cells: u32) -> DynamicImage {
+    let cell_size = (size / cells).max(1);
+
+    let image = RgbImage::from_fn(size, size, |x, y| {
+        if (x / cell_size + y / cell_size).is_multiple_of(2) {
+            Rgb([255, 255, 255])
+        } else {
+            Rgb([96, 96, 96])
+        }
+    });
+
+    DynamicImage::ImageRgb8(image)
+}
+
+/// `cells` by `cells` grid, every cell is tinted by its position and carries its number.
+///
+/// The digits are drawn mirrored and upside down in image space: rows are uploaded bottom-up and
+/// `u` grows to the left when looking at the figure from outside, so they read normally there.
+fn uv_grid(size: u32, cells: u32) -> DynamicImage {
+    let cell_size = size / cells;
+    let line_width = (cell_size / 32).max(1);
+    let digit_scale = (cell_size / 12).max(1);
+
+    let mut image = RgbImage::from_fn(size, size, |x, y| {
+        let (column, row) = (x / cell_size, y / cell_size);
+        let (cell_x, cell_y) = (x % cell_size, y % cell_size);
+
+        if cell_x < line_width || cell_y < line_width {
+            return Rgb([20, 20, 20]);
+        }
+
+        let tint = Vec3::new(
+            (column as f32 + 0.5) / cells as f32,
+            (row as f32 + 0.5) / cells as f32,
+            0.6,
+        );
+        to_rgb(tint * 0.6 + Vec3::splat(0.3))
+    });
+
+    for row in 0..cells {
+        for column in 0..cells {
+            let number = row * cells + column;
+            let digits = number.to_string();
+            let text_width = digits.len() as u32 * 4 * digit_scale;
+
+            let origin_x = column * cell_size + (cell_size + text_width) / 2;
+            let origin_y = row * cell_size + (cell_size - 5 * digit_scale) / 2;
+
+            for (index, digit) in digits.bytes().enumerate() {
+                let glyph = DIGIT_GLYPHS[(digit - b'0') as usize];
+                let glyph_x = origin_x - index as u32 * 4 * digit_scale;
+
+                for (glyph_row, bits) in glyph.iter().enumerate() {
+                    for glyph_column in 0..3 {
+                        if bits & (0b100 >> glyph_column) == 0 {
+                            continue;
+                        }
+
+                        let x = glyph_x - (glyph_column + 1) * digit_scale;
+                        let y = origin_y + (4 - glyph_row as u32) * digit_scale;
+                        for dy in 0..digit_scale {
+                            for dx in 0..digit_scale {
+                                image.put_pixel(x + dx, y + dy, Rgb([0, 0, 0]));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    DynamicImage::ImageRgb8(image)
+}
+
+/// 3x5 pixel font, one row of three bits per entry, top row first.
+const DIGIT_GLYPHS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+fn uv_gradient(size: u32) -> DynamicImage {
+    let image = RgbImage::from_fn(size, size, |x, y| {
+        let uv = Vec2::new(x as f32, y as f32) / size as f32;
+        to_rgb(Vec3::new(uv.x, uv.y, 1.0 - uv.x.max(uv.y)))
+    });
+
+    DynamicImage::ImageRgb8(image)
+}
+
+/// Grayscale fractal Perlin noise, seamless in both directions.
+fn noise(size: u32) -> DynamicImage {
+    const PERIOD: u32 = 8;
+
+    let image = RgbImage::from_fn(size, size, |x, y| {
+        let point = Vec2::new(x as f32, y as f32) / size as f32 * PERIOD as f32;
+        let value = fractal_noise(point, PERIOD, 5) * 0.5 + 0.5;
+
+        to_rgb(Vec3::splat(value))
+    });
+
+    DynamicImage::ImageRgb8(image)
+}
+
+/// White marble with dark veins, bands along `u` bent by fractal noise.
+fn marble(size: u32) -> DynamicImage {
+    const PERIOD: u32 = 4;
+    const BANDS: f32 = 6.0;
+    const TURBULENCE: f32 = 1.2;
+
+    let stone = Vec3::new(0.93, 0.91, 0.87);
+    let vein = Vec3::new(0.25, 0.3, 0.3);
+
+    let image = RgbImage::from_fn(size, size, |x, y| {
+        let uv = Vec2::new(x as f32, y as f32) / size as f32;
+        let turbulence = fractal_noise(uv * PERIOD as f32, PERIOD, 4);
+        let bands = (std::f32::consts::TAU * (uv.x * BANDS + turbulence * TURBULENCE)).sin();
+
+        // Sharpen the dark part of the bands into thin veins.
+        let veins = (1.0 - bands.abs()).powf(4.0);
+        to_rgb(stone.lerp(vein, veins))
+    });
+
+    DynamicImage::ImageRgb8(image)
+}
+
+/// Normal map of a grid of round bumps, used when no normal map is configured.
+pub(crate) fn bumps_normal_map() -> DynamicImage {
+    const SIZE: u32 = 512;
+    /// Bumps along each side of the map.
+    const BUMPS: f32 = 32.0;
+    const HEIGHT: f32 = 0.04;
+
+    let frequency = 2.0 * std::f32::consts::PI * BUMPS;
+    let image = RgbImage::from_fn(SIZE, SIZE, |x, y| {
+        let u = x as f32 / SIZE as f32;
+        let v = y as f32 / SIZE as f32;
+
+        // Gradient of the height field `HEIGHT * sin(frequency * u) * sin(frequency * v)`.
+        let du = HEIGHT * frequency * (frequency * u).cos() * (frequency * v).sin();
+        let dv = HEIGHT * frequency * (frequency * u).sin() * (frequency * v).cos();
+        let normal = Vec3::new(-du, -dv, 1.0).normalize();
+
+        to_rgb(normal * 0.5 + Vec3::splat(0.5))
+    });
+
+    DynamicImage::ImageRgb8(image)
+}
+
+/// Sum of `octaves` layers of [`perlin`] noise, each at double the frequency and half the
+/// amplitude of the previous one. Roughly in `[-1, 1]`.
+fn fractal_noise(point: Vec2, period: u32, octaves: u32) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 0.5;
+    let mut frequency = 1;
+
+    for _ in 0..octaves {
+        value += amplitude * perlin(point * frequency as f32, period * frequency);
+        amplitude *= 0.5;
+        frequency *= 2;
+    }
+
+    value
+}
+
+/// Gradient noise repeating every `period` units along both axes, in `[-1, 1]`.
+fn perlin(point: Vec2, period: u32) -> f32 {
+    let cell = point.floor();
+    let offset = point - cell;
+    let (x, y) = (cell.x as i64, cell.y as i64);
+
+    let corner = |dx: i64, dy: i64| {
+        let gradient = lattice_gradient(
+            (x + dx).rem_euclid(period as i64) as u32,
+            (y + dy).rem_euclid(period as i64) as u32,
+        );
+        gradient.dot(offset - Vec2::new(dx as f32, dy as f32))
+    };
+
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (u, v) = (fade(offset.x), fade(offset.y));
+
+    let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * u;
+    let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * u;
+
+    // The largest value of 2D Perlin noise is sqrt(0.5).
+    (bottom + (top - bottom) * v) * std::f32::consts::SQRT_2
+}
+
+/// Pseudo-random unit vector attached to a lattice point.
+fn lattice_gradient(x: u32, y: u32) -> Vec2 {
+    let mut hash = x.wrapping_mul(0x8da6_b343) ^ y.wrapping_mul(0xd816_3841);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+
+    let angle = hash as f32 / u32::MAX as f32 * std::f32::consts::TAU;
+    Vec2::new(angle.cos(), angle.sin())
+}
+
+fn to_rgb(color: Vec3) -> Rgb<u8> {
+    let color = (color.clamp(Vec3::ZERO, Vec3::ONE) * 255.0).round();
+    Rgb([color.x as u8, color.y as u8, color.z as u8])
+}
@@ -0,0 +1,181 @@
+use glam::{Mat4, Vec4};
+use serde::Deserialize;
+
+/// Remaps clip space depth from the `[0, 1]` range of glam's left-handed projections to the
+/// `[-1, 1]` range GL clips against. `glClipControl` would avoid this but GLES lacks it.
+const ZERO_TO_ONE_TO_GL_DEPTH: Mat4 = Mat4::from_cols(
+    Vec4::X,
+    Vec4::Y,
+    Vec4::new(0.0, 0.0, 2.0, 0.0),
+    Vec4::new(0.0, 0.0, -1.0, 1.0),
+);
+
+/// Left-handed perspective projection mapping depth into GL's `[-1, 1]`.
+pub(crate) fn perspective_lh_gl(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Mat4 {
+    ZERO_TO_ONE_TO_GL_DEPTH * Mat4::perspective_lh(fov_y, aspect_ratio, near, far)
+}
+
+/// Left-handed orthographic projection mapping depth into GL's `[-1, 1]`.
+pub(crate) fn orthographic_lh_gl(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    near: f32,
+    far: f32,
+) -> Mat4 {
+    ZERO_TO_ONE_TO_GL_DEPTH * Mat4::orthographic_lh(left, right, bottom, top, near, far)
+}
+
+/// Projection parameters shared by both projection modes.
+///
+/// Everything is left-handed, matching [`Mat4::look_at_lh`] used for the view: the camera looks
+/// along `+Z` and depth is mapped into GL's default `[-1, 1]` in both modes.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ProjectionConfig {
+    /// Vertical field of view in radians.
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl ProjectionConfig {
+    pub fn perspective(&self, aspect_ratio: f32) -> Mat4 {
+        perspective_lh_gl(self.fov_y, aspect_ratio, self.near, self.far)
+    }
+
+    /// Orthographic projection matching the perspective frustum cross-section at `distance`,
+    /// so the plane through the camera target looks the same in both modes.
+    pub fn axonometric(&self, aspect_ratio: f32, distance: f32) -> Mat4 {
+        let half_height = self.half_height_at(distance);
+        let half_width = half_height * aspect_ratio;
+
+        orthographic_lh_gl(
+            -half_width,
+            half_width,
+            -half_height,
+            half_height,
+            self.near,
+            self.far,
+        )
+    }
+
+    /// Half the height of the view at `distance` from the camera, the same in both modes.
+    pub fn half_height_at(&self, distance: f32) -> f32 {
+        distance * (self.fov_y / 2.0).tan()
+    }
+
+    /// Linear blend between [`Self::perspective`] (`blend = 0.0`) and [`Self::axonometric`]
+    /// (`blend = 1.0`). Points on the plane at `distance` stay in place during the blend.
+    pub fn blended(&self, aspect_ratio: f32, distance: f32, blend: f32) -> Mat4 {
+        self.perspective(aspect_ratio) * (1.0 - blend)
+            + self.axonometric(aspect_ratio, distance) * blend
+    }
+}
+
+impl Default for ProjectionConfig {
+    fn default() -> Self {
+        Self {
+            fov_y: std::f32::consts::FRAC_PI_4,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Mat4, Vec3};
+
+    use super::ProjectionConfig;
+
+    const ASPECT_RATIO: f32 = 16.0 / 9.0;
+    const DISTANCE: f32 = 4.5;
+    const EPSILON: f32 = 1e-4;
+
+    fn project(projection: Mat4, point: Vec3) -> Vec3 {
+        let view = Mat4::look_at_lh(Vec3::new(0.0, 0.0, -DISTANCE), Vec3::ZERO, Vec3::Y);
+        projection.project_point3(view.transform_point3(point))
+    }
+
+    fn modes(config: &ProjectionConfig) -> [Mat4; 3] {
+        [
+            config.perspective(ASPECT_RATIO),
+            config.axonometric(ASPECT_RATIO, DISTANCE),
+            config.blended(ASPECT_RATIO, DISTANCE, 0.5),
+        ]
+    }
+
+    fn assert_xy_eq(actual: Vec3, expected: Vec3) {
+        assert!(
+            (actual.x - expected.x).abs() < EPSILON && (actual.y - expected.y).abs() < EPSILON,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn target_projects_to_center() {
+        let config = ProjectionConfig::default();
+
+        for projection in modes(&config) {
+            assert_xy_eq(project(projection, Vec3::ZERO), Vec3::ZERO);
+        }
+    }
+
+    #[test]
+    fn target_plane_edges_match_in_both_modes() {
+        let config = ProjectionConfig {
+            fov_y: std::f32::consts::FRAC_PI_3,
+            near: 0.5,
+            far: 30.0,
+        };
+        let half_height = DISTANCE * (config.fov_y / 2.0).tan();
+        let half_width = half_height * ASPECT_RATIO;
+
+        for projection in modes(&config) {
+            assert_xy_eq(
+                project(projection, Vec3::new(0.0, half_height, 0.0)),
+                Vec3::new(0.0, 1.0, 0.0),
+            );
+            assert_xy_eq(
+                project(projection, Vec3::new(half_width, 0.0, 0.0)),
+                Vec3::new(1.0, 0.0, 0.0),
+            );
+            assert_xy_eq(
+                project(projection, Vec3::new(0.3, -0.2, 0.0)),
+                project(config.perspective(ASPECT_RATIO), Vec3::new(0.3, -0.2, 0.0)),
+            );
+        }
+    }
+
+    #[test]
+    fn clip_planes_map_to_gl_depth_range() {
+        let config = ProjectionConfig::default();
+        let near = Vec3::new(0.0, 0.0, config.near - DISTANCE);
+        let far = Vec3::new(0.0, 0.0, config.far - DISTANCE);
+        let between = Vec3::new(0.0, 0.0, (config.near + config.far) / 2.0 - DISTANCE);
+
+        for projection in [
+            config.perspective(ASPECT_RATIO),
+            config.axonometric(ASPECT_RATIO, DISTANCE),
+        ] {
+            assert!((project(projection, near).z + 1.0).abs() < EPSILON);
+            assert!((project(projection, far).z - 1.0).abs() < EPSILON);
+            // Inside the clip volume, the whole range between the planes is kept.
+            assert!(project(projection, between).z.abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn nearer_points_are_in_front_in_both_modes() {
+        let config = ProjectionConfig::default();
+
+        for projection in modes(&config) {
+            let front = project(projection, Vec3::new(0.0, 0.0, -1.0));
+            let back = project(projection, Vec3::new(0.0, 0.0, 1.0));
+
+            assert!(front.z < back.z);
+        }
+    }
+}
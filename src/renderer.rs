@@ -1,80 +1,116 @@
 use gl_window_provider::Renderer;
-use glam::{Mat4, Vec3};
-use std::ffi::CString;
+use glam::{Mat4, Quat, Vec3};
+use std::{ffi::CString, time::Instant};
 use winit::{
     dpi::PhysicalPosition,
     event::{
-        ElementState, KeyboardInput, MouseButton, MouseScrollDelta, TouchPhase, VirtualKeyCode,
+        ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
+        VirtualKeyCode,
     },
 };
 
 use crate::{
-    array::{AttribPointer, Size, VerticesArray},
+    array::{AttribPointer, ElementsArray, Size, VerticesArray},
+    bookmarks::Bookmarks,
+    camera::{Camera, Projection},
+    cli::Args,
+    input::InputConfig,
+    light::{Lights, PointLight},
+    light_editor::LightEditor,
+    material::{Environment, Material, MaterialMaps, PbrMaps, PbrMaterial, MATERIAL_PRESETS},
+    procedural::ProceduralTexture,
+    scene::{FigureConfig, Scene, SceneWatcher},
     shader_program::{ShaderProgram, ShaderProgramBuilder},
-    texture::Texture,
+    shadow::Shadows,
+    skybox::Skybox,
+    texture,
+    uv::{UvMapping, UvProjection},
 };
 
-enum Projection {
-    Perspective,
-    Axonometric,
-}
-
 enum ModelKind {
     Color,
     Texture,
 }
 
-struct DirLight {
-    direction: Vec3,
-    ambient: Vec3,
-    diffuse: Vec3,
-    specular: Vec3,
+/// Values must match the `SHADING_*` defines in `polygon_program/lighting.glsl`.
+#[derive(Clone, Copy, Debug)]
+enum ShadingModel {
+    Flat = 0,
+    Gouraud = 1,
+    Phong = 2,
+    BlinnPhong = 3,
 }
 
-struct PointLight {
-    position: Vec3,
-    ambient: Vec3,
-    diffuse: Vec3,
-    specular: Vec3,
-    constant: f32,
-    linear: f32,
-    quadratic: f32,
+impl ShadingModel {
+    fn next(self) -> Self {
+        match self {
+            Self::Flat => Self::Gouraud,
+            Self::Gouraud => Self::Phong,
+            Self::Phong => Self::BlinnPhong,
+            Self::BlinnPhong => Self::Flat,
+        }
+    }
 }
 
-struct Material {
-    ambient: Vec3,
-    diffuse: Vec3,
-    specular: Vec3,
-    shininess: f32,
+#[derive(Clone, Copy, Debug)]
+enum MaterialMode {
+    Classic,
+    Pbr,
 }
 
 pub(crate) struct RgzRenderer {
     gl: gl::Gl,
 
-    texture: Texture,
-
-    model_slices_count: u32,
-    mesh_slices_count: u32,
-    figure_radius: f32,
+    figure: FigureConfig,
 
     mesh_array: VerticesArray,
     mesh_program: ShaderProgram,
 
     polygon_array: VerticesArray,
     polygon_program: ShaderProgram,
+    pbr_program: ShaderProgram,
+
+    marker_array: VerticesArray,
+    marker_elements: ElementsArray,
+    marker_program: ShaderProgram,
 
     left_mouse_btn_pressed: bool,
+    right_mouse_btn_pressed: bool,
     last_cursor_pos: Option<PhysicalPosition<f64>>,
     cursor_left: bool,
 
-    camera_polar_angle: f32,
-    camera_azimuthal_angle: f32,
-    camera_zoom: f32,
+    input_config: InputConfig,
+    window_height: u32,
+    scale_factor: f64,
+
+    camera: Camera,
+    last_frame_instant: Option<Instant>,
+    bookmarks: Bookmarks,
+    modifiers: ModifiersState,
 
-    projection: Projection,
     show_mesh: bool,
     show_model: bool,
     model_kind: ModelKind,
+    shading_model: ShadingModel,
+    material_mode: MaterialMode,
+    /// Index into [`MATERIAL_PRESETS`] of the preset the classic material is based on.
+    material_preset: usize,
+    material: Material,
+    material_maps: MaterialMaps,
+    normal_mapping: bool,
+    environment: Environment,
+    uv: UvMapping,
+    /// Encodes the output as sRGB, off writes the linear lighting result as is.
+    gamma_correction: bool,
+    pbr_material: PbrMaterial,
+    pbr_maps: PbrMaps,
+
+    lights: Lights,
+    light_editor: LightEditor,
+    shadows: Shadows,
+    skybox: Skybox,
+
+    scene_watcher: SceneWatcher,
 }
 
 impl RgzRenderer {
@@ -87,44 +123,99 @@ impl RgzRenderer {
     const USE_COLOR_MODEL_KEYCODE: VirtualKeyCode = VirtualKeyCode::Key5;
     const USE_TEXTURE_MODEL_KEYCODE: VirtualKeyCode = VirtualKeyCode::Key6;
 
-    const MODEL_SLICES_COUNT: u32 = 1000;
-    const MESH_SLICES_COUNT: u32 = 100;
-    const FIGURE_RADIUS: f32 = 1.0;
-
-    const ZOOM_FACTOR: f32 = 1.0 / 10.0;
-    const ZOOM_MIN: f32 = -20.0;
-    const ZOOM_DEFAULT: f32 = -4.5;
-    const ZOOM_MAX: f32 = -2.0;
-
-    const DELTA_X_INTO_DELTA_ANGLE_FACTOR: f32 = std::f32::consts::FRAC_PI_2 / (1920.0 / 2.0);
-    const DELTA_Y_INTO_DELTA_ANGLE_FACTOR: f32 = std::f32::consts::FRAC_PI_2 / (1280.0 / 2.0);
-
-    const POLAR_ANGLE_MAX: f32 = std::f32::consts::FRAC_PI_2 * 0.9;
-    const POLAR_ANGLE_MIN: f32 = -Self::POLAR_ANGLE_MAX;
-
-    const MATERIAL: Material = Material {
-        ambient: Vec3::new(0.1745, 0.01175, 0.01175),
-        diffuse: Vec3::new(0.61424, 0.04136, 0.04136),
-        specular: Vec3::new(0.727811, 0.626959, 0.626959),
-        shininess: 0.6,
-    };
-
-    const DIR_LIGHT: DirLight = DirLight {
-        direction: Vec3::new(0.0, 1.0, 0.0),
-        ambient: Vec3::splat(0.1),
-        diffuse: Vec3::splat(0.2),
-        specular: Vec3::splat(0.4),
-    };
-
-    const POINT_LIGHT: PointLight = PointLight {
-        position: Vec3::new(1.0, 1.0, 1.0),
-        ambient: Vec3::splat(0.1),
-        diffuse: Vec3::splat(0.4),
-        specular: Vec3::splat(0.5),
-        constant: 1.0,
-        linear: 0.09,
-        quadratic: 0.032,
-    };
+    const NEXT_SHADING_MODEL_KEYCODE: VirtualKeyCode = VirtualKeyCode::M;
+
+    /// Selects the next classic material preset, with Shift held the previous one.
+    const NEXT_MATERIAL_PRESET_KEYCODE: VirtualKeyCode = VirtualKeyCode::N;
+
+    /// Cycles the diffuse map through the procedural textures and back to the loaded one, with
+    /// Shift held in reverse.
+    const NEXT_PROCEDURAL_TEXTURE_KEYCODE: VirtualKeyCode = VirtualKeyCode::T;
+
+    const TOGGLE_NORMAL_MAPPING_KEYCODE: VirtualKeyCode = VirtualKeyCode::B;
+    const DECREASE_NORMAL_STRENGTH_KEYCODE: VirtualKeyCode = VirtualKeyCode::Z;
+    const INCREASE_NORMAL_STRENGTH_KEYCODE: VirtualKeyCode = VirtualKeyCode::X;
+    const NORMAL_STRENGTH_STEP: f32 = 0.25;
+
+    /// Selects the next texture coordinate projection, with Shift held the previous one.
+    const NEXT_UV_PROJECTION_KEYCODE: VirtualKeyCode = VirtualKeyCode::U;
+    const NEXT_UV_PLANAR_AXIS_KEYCODE: VirtualKeyCode = VirtualKeyCode::Y;
+    const DECREASE_UV_SCALE_KEYCODE: VirtualKeyCode = VirtualKeyCode::Q;
+    const INCREASE_UV_SCALE_KEYCODE: VirtualKeyCode = VirtualKeyCode::W;
+    const UV_SCALE_FACTOR: f32 = 1.25;
+    const ROTATE_UV_CLOCKWISE_KEYCODE: VirtualKeyCode = VirtualKeyCode::A;
+    const ROTATE_UV_COUNTERCLOCKWISE_KEYCODE: VirtualKeyCode = VirtualKeyCode::D;
+    const UV_ROTATION_STEP: f32 = std::f32::consts::PI / 12.0;
+
+    /// Cycles between the lit material, a mirror and a refractive figure showing the skybox.
+    const NEXT_ENVIRONMENT_MODE_KEYCODE: VirtualKeyCode = VirtualKeyCode::R;
+    const TOGGLE_ENVIRONMENT_FRESNEL_KEYCODE: VirtualKeyCode = VirtualKeyCode::F;
+
+    const TOGGLE_GAMMA_CORRECTION_KEYCODE: VirtualKeyCode = VirtualKeyCode::G;
+
+    const TOGGLE_MATERIAL_MODE_KEYCODE: VirtualKeyCode = VirtualKeyCode::P;
+    const DECREASE_ROUGHNESS_KEYCODE: VirtualKeyCode = VirtualKeyCode::Comma;
+    const INCREASE_ROUGHNESS_KEYCODE: VirtualKeyCode = VirtualKeyCode::Period;
+    const DECREASE_METALLIC_KEYCODE: VirtualKeyCode = VirtualKeyCode::Semicolon;
+    const INCREASE_METALLIC_KEYCODE: VirtualKeyCode = VirtualKeyCode::Apostrophe;
+    const PBR_PARAMETER_STEP: f32 = 0.05;
+
+    const TOGGLE_ROTATION_INERTIA_KEYCODE: VirtualKeyCode = VirtualKeyCode::I;
+    /// Moves the look-at target back to the origin after panning with the right mouse button.
+    const RESET_CAMERA_TARGET_KEYCODE: VirtualKeyCode = VirtualKeyCode::O;
+
+    /// Switch every light of a kind off when any of them is on, otherwise all of them on.
+    const TOGGLE_DIR_LIGHTS_KEYCODE: VirtualKeyCode = VirtualKeyCode::Key7;
+    const TOGGLE_POINT_LIGHTS_KEYCODE: VirtualKeyCode = VirtualKeyCode::Key8;
+    const TOGGLE_SPOT_LIGHTS_KEYCODE: VirtualKeyCode = VirtualKeyCode::Key9;
+    /// Adds a point light, with Shift held removes the last one instead.
+    const ADD_POINT_LIGHT_KEYCODE: VirtualKeyCode = VirtualKeyCode::L;
+
+    const TOGGLE_LIGHT_EDIT_MODE_KEYCODE: VirtualKeyCode = VirtualKeyCode::E;
+    const SELECT_NEXT_LIGHT_KEYCODE: VirtualKeyCode = VirtualKeyCode::Tab;
+    const CYCLE_LIGHT_COLOR_KEYCODE: VirtualKeyCode = VirtualKeyCode::C;
+    const TOGGLE_SELECTED_LIGHT_KEYCODE: VirtualKeyCode = VirtualKeyCode::Space;
+    const WEAKEN_ATTENUATION_KEYCODE: VirtualKeyCode = VirtualKeyCode::LBracket;
+    const STRENGTHEN_ATTENUATION_KEYCODE: VirtualKeyCode = VirtualKeyCode::RBracket;
+    const TOGGLE_SHADOWS_KEYCODE: VirtualKeyCode = VirtualKeyCode::H;
+    const DECREASE_SHADOW_BIAS_KEYCODE: VirtualKeyCode = VirtualKeyCode::Minus;
+    const INCREASE_SHADOW_BIAS_KEYCODE: VirtualKeyCode = VirtualKeyCode::Equals;
+
+    /// Distance a light moves per arrow or PageUp/PageDown press in light-edit mode.
+    const LIGHT_MOVE_STEP: f32 = 0.1;
+
+    const MARKER_SCALE: f32 = 0.04;
+    const SELECTED_MARKER_SCALE: f32 = 0.07;
+
+    /// Pressing one of these recalls the bookmark in the matching slot, with Shift held the
+    /// current view is stored there instead.
+    const BOOKMARK_SLOT_KEYCODES: [VirtualKeyCode; 9] = [
+        VirtualKeyCode::F1,
+        VirtualKeyCode::F2,
+        VirtualKeyCode::F3,
+        VirtualKeyCode::F4,
+        VirtualKeyCode::F5,
+        VirtualKeyCode::F6,
+        VirtualKeyCode::F7,
+        VirtualKeyCode::F8,
+        VirtualKeyCode::F9,
+    ];
+
+    /// Upper bound for a single frame step, so a stalled frame doesn't make the camera jump.
+    const MAX_FRAME_TIME: f32 = 0.1;
+
+    /// Ambient radiance of the PBR mode, standing in for the light bounced off the environment.
+    const PBR_AMBIENT: Vec3 = Vec3::splat(0.03);
+    /// Directory the optional PBR texture maps are loaded from.
+    const PBR_MAPS_DIR: &str = "pbr";
+
+    /// Diffuse colors given to the point lights added at runtime, in turn.
+    const EXTRA_POINT_LIGHT_COLORS: [Vec3; 4] = [
+        Vec3::new(0.4, 0.1, 0.1),
+        Vec3::new(0.1, 0.4, 0.1),
+        Vec3::new(0.1, 0.1, 0.4),
+        Vec3::new(0.4, 0.4, 0.1),
+    ];
 }
 
 impl Renderer for RgzRenderer {
@@ -137,26 +228,10 @@ impl Renderer for RgzRenderer {
             gl_display.get_proc_address(symbol.as_c_str()).cast()
         });
 
-        let polygon_slices_count = Self::MODEL_SLICES_COUNT;
-        let mesh_slices_count = Self::MESH_SLICES_COUNT;
-        let radius = Self::FIGURE_RADIUS;
-
-        let mut polygon_angles = Vec::new();
-        let mut mesh_angles = Vec::new();
-
-        for polar_index in 0..polygon_slices_count {
-            for azimuth_index in 0..polygon_slices_count {
-                polygon_angles.push(polar_index);
-                polygon_angles.push(azimuth_index);
-            }
-        }
-
-        for polar_index in 0..mesh_slices_count {
-            for azimuth_index in 0..mesh_slices_count {
-                mesh_angles.push(polar_index);
-                mesh_angles.push(azimuth_index);
-            }
-        }
+        let scene = Scene::load(Scene::FILE_NAME).unwrap_or_else(|err| {
+            eprintln!("{err}, using the default scene");
+            Scene::default()
+        });
 
         let mesh_program = ShaderProgramBuilder::new(gl.clone())
             .vertex_shader(include_bytes!("mesh_program/vertex_shader.glsl"))
@@ -166,119 +241,195 @@ impl Renderer for RgzRenderer {
             .unwrap();
 
         let polygon_program = ShaderProgramBuilder::new(gl.clone())
+            .include(
+                "lighting.glsl",
+                include_bytes!("polygon_program/lighting.glsl"),
+            )
+            .include(
+                "surface.glsl",
+                include_bytes!("polygon_program/surface.glsl"),
+            )
+            .include("output.glsl", include_bytes!("polygon_program/output.glsl"))
             .vertex_shader(include_bytes!("polygon_program/vertex_shader.glsl"))
             .geometry_shader(include_bytes!("polygon_program/geometry_shader.glsl"))
             .fragment_shader(include_bytes!("polygon_program/fragment_shader.glsl"))
             .build()
             .unwrap();
 
-        mesh_program.use_program();
-        polygon_program.use_program();
+        let pbr_program = ShaderProgramBuilder::new(gl.clone())
+            .include(
+                "lighting.glsl",
+                include_bytes!("polygon_program/lighting.glsl"),
+            )
+            .include(
+                "surface.glsl",
+                include_bytes!("polygon_program/surface.glsl"),
+            )
+            .include("output.glsl", include_bytes!("polygon_program/output.glsl"))
+            .vertex_shader(include_bytes!("polygon_program/vertex_shader.glsl"))
+            .geometry_shader(include_bytes!("polygon_program/geometry_shader.glsl"))
+            .fragment_shader(include_bytes!("polygon_program/pbr_fragment_shader.glsl"))
+            .build()
+            .unwrap();
 
-        let polygon_array = VerticesArray::new(gl.clone(), polygon_angles);
-        let mesh_array = VerticesArray::new(gl.clone(), mesh_angles);
+        let polygon_array = Self::slices_array(&gl, scene.figure.model_slices, &polygon_program);
+        let mesh_array = Self::slices_array(&gl, scene.figure.mesh_slices, &mesh_program);
 
-        polygon_array.use_array();
-        mesh_array.use_array();
+        let marker_program = ShaderProgramBuilder::new(gl.clone())
+            .vertex_shader(include_bytes!("marker_program/vertex_shader.glsl"))
+            .fragment_shader(include_bytes!("marker_program/fragment_shader.glsl"))
+            .build()
+            .unwrap();
 
-        polygon_array.set_attrib_int_pointer(
-            polygon_program.attrib_location_of("iPolarAngleIndex"),
+        #[rustfmt::skip]
+        let cube_vertices: Vec<f32> = vec![
+            -1.0, -1.0, -1.0,
+             1.0, -1.0, -1.0,
+             1.0,  1.0, -1.0,
+            -1.0,  1.0, -1.0,
+            -1.0, -1.0,  1.0,
+             1.0, -1.0,  1.0,
+             1.0,  1.0,  1.0,
+            -1.0,  1.0,  1.0,
+        ];
+
+        #[rustfmt::skip]
+        let cube_indices: [u16; 36] = [
+            0, 1, 2, 2, 3, 0,
+            4, 5, 6, 6, 7, 4,
+            0, 4, 7, 7, 3, 0,
+            1, 5, 6, 6, 2, 1,
+            3, 2, 6, 6, 7, 3,
+            0, 1, 5, 5, 4, 0,
+        ];
+
+        // The elements buffer gets attached to the vertex array bound right before it.
+        let marker_array = VerticesArray::new(gl.clone(), cube_vertices);
+        let marker_elements = ElementsArray::new(gl.clone(), &cube_indices);
+
+        marker_array.set_attrib_pointer(
+            marker_program.attrib_location_of("iPosition"),
             AttribPointer {
-                size: Size::One,
-                stride: 2 * std::mem::size_of::<u32>(),
+                size: Size::Three,
+                stride: 3 * std::mem::size_of::<f32>(),
                 offset: 0,
-                ty: gl::UNSIGNED_INT,
-            },
-        );
-        mesh_array.set_attrib_int_pointer(
-            mesh_program.attrib_location_of("iPolarAngleIndex"),
-            AttribPointer {
-                size: Size::One,
-                stride: 2 * std::mem::size_of::<u32>(),
-                offset: 0,
-                ty: gl::UNSIGNED_INT,
+                ty: gl::FLOAT,
             },
+            false,
         );
 
-        polygon_array.set_attrib_int_pointer(
-            polygon_program.attrib_location_of("iAzimuthAngleIndex"),
-            AttribPointer {
-                size: Size::One,
-                stride: 2 * std::mem::size_of::<u32>(),
-                offset: std::mem::size_of::<u32>(),
-                ty: gl::UNSIGNED_INT,
-            },
-        );
-        mesh_array.set_attrib_int_pointer(
-            mesh_program.attrib_location_of("iAzimuthAngleIndex"),
-            AttribPointer {
-                size: Size::One,
-                stride: 2 * std::mem::size_of::<u32>(),
-                offset: std::mem::size_of::<u32>(),
-                ty: gl::UNSIGNED_INT,
-            },
-        );
+        let bookmarks = Bookmarks::load(Bookmarks::FILE_NAME).unwrap_or_else(|err| {
+            eprintln!("{err}, bookmarks won't be saved during this session");
+            Bookmarks::in_memory()
+        });
 
-        let texture = Texture::open(gl.clone(), "/home/danilka108/labs/graphics_rgz/texture.jpg");
+        let pbr_maps = PbrMaps::load_dir(&gl, Self::PBR_MAPS_DIR);
 
-        Self {
-            texture,
+        let mut renderer = Self {
+            shadows: Shadows::new(gl.clone()),
+            skybox: Skybox::new(gl.clone()),
             gl,
 
-            figure_radius: radius,
-            model_slices_count: polygon_slices_count,
-            mesh_slices_count,
+            figure: scene.figure,
 
             polygon_array,
             polygon_program,
+            pbr_program,
 
             mesh_array,
             mesh_program,
 
+            marker_array,
+            marker_elements,
+            marker_program,
+
             left_mouse_btn_pressed: false,
+            right_mouse_btn_pressed: false,
             last_cursor_pos: None,
             cursor_left: false,
 
-            camera_polar_angle: 0.0,
-            camera_azimuthal_angle: 0.0,
-            camera_zoom: Self::ZOOM_DEFAULT,
+            input_config: scene.input,
+            window_height: 1,
+            scale_factor: 1.0,
+
+            camera: Camera::new(scene.camera.projection),
+            last_frame_instant: None,
+            bookmarks,
+            modifiers: ModifiersState::empty(),
 
-            projection: Projection::Perspective,
             show_mesh: true,
             show_model: true,
             model_kind: ModelKind::Color,
-        }
+            shading_model: ShadingModel::Phong,
+            material_mode: MaterialMode::Classic,
+            material_preset: 0,
+            material: MATERIAL_PRESETS[0].material,
+            material_maps: MaterialMaps::default(),
+            normal_mapping: false,
+            environment: Environment::default(),
+            uv: UvMapping::default(),
+            gamma_correction: true,
+            pbr_material: PbrMaterial::default(),
+            pbr_maps,
+
+            lights: Lights::default(),
+            light_editor: LightEditor::new(),
+
+            scene_watcher: SceneWatcher::new(Scene::FILE_NAME),
+        };
+
+        renderer.apply_scene(scene);
+        renderer
     }
 
     fn mouse_input_hook(&mut self, state: ElementState, button: MouseButton) {
-        self.left_mouse_btn_pressed =
-            matches!(state, ElementState::Pressed) && matches!(button, MouseButton::Left);
+        let pressed = matches!(state, ElementState::Pressed);
+
+        match button {
+            MouseButton::Left => {
+                self.left_mouse_btn_pressed = pressed;
+
+                if pressed && !self.light_editor.is_active() {
+                    self.camera.begin_drag();
+                } else {
+                    self.camera.end_drag();
+                }
+            }
+            MouseButton::Right => self.right_mouse_btn_pressed = pressed,
+            _ => {}
+        }
     }
 
     fn mouse_wheel_hook(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {
-        let MouseScrollDelta::LineDelta(_, vertical_delta) = delta else {
-            return;
-        };
-
-        let TouchPhase::Moved = phase else {
-            return;
-        };
+        if let Some(zoom_delta) = self
+            .input_config
+            .zoom_delta(delta, phase, self.scale_factor)
+        {
+            self.camera.zoom_by(zoom_delta);
+        }
+    }
 
-        self.camera_zoom = (self.camera_zoom + vertical_delta * Self::ZOOM_FACTOR)
-            .clamp(Self::ZOOM_MIN, Self::ZOOM_MAX);
+    fn scale_factor_changed_hook(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
     }
 
     fn keyboard_input_hook(&mut self, input: KeyboardInput) {
+        if let (Some(keycode), ElementState::Pressed) = (input.virtual_keycode, input.state) {
+            if self.light_editor.is_active() && self.light_edit_input(keycode) {
+                return;
+            }
+        }
+
         match input.virtual_keycode {
             Some(Self::USE_AXONOMETRIC_PROJ_KEYCODE)
                 if matches!(input.state, ElementState::Pressed) =>
             {
-                self.projection = Projection::Axonometric
+                self.camera.set_projection(Projection::Axonometric)
             }
             Some(Self::USE_PERSPECTIVE_PROJ_KEYCODE)
                 if matches!(input.state, ElementState::Pressed) =>
             {
-                self.projection = Projection::Perspective
+                self.camera.set_projection(Projection::Perspective)
             }
             Some(Self::TOGGLE_MESH_DISPLAY_KEYCODE)
                 if matches!(input.state, ElementState::Pressed) =>
@@ -298,13 +449,222 @@ impl Renderer for RgzRenderer {
             {
                 self.model_kind = ModelKind::Texture;
             }
+            Some(Self::NEXT_SHADING_MODEL_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.shading_model = self.shading_model.next();
+                println!("shading model: {:?}", self.shading_model);
+            }
+            Some(Self::NEXT_MATERIAL_PRESET_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                let step = if self.modifiers.shift() {
+                    MATERIAL_PRESETS.len() - 1
+                } else {
+                    1
+                };
+                self.material_preset = (self.material_preset + step) % MATERIAL_PRESETS.len();
+                self.material = MATERIAL_PRESETS[self.material_preset].material;
+                println!(
+                    "material preset: {}",
+                    MATERIAL_PRESETS[self.material_preset].name
+                );
+            }
+            Some(Self::NEXT_PROCEDURAL_TEXTURE_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.cycle_procedural_texture();
+            }
+            Some(Self::TOGGLE_NORMAL_MAPPING_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.normal_mapping = !self.normal_mapping;
+                println!("normal mapping: {}", self.normal_mapping);
+            }
+            Some(Self::NEXT_UV_PROJECTION_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                let projections = UvProjection::ALL;
+                let current = projections
+                    .iter()
+                    .position(|&projection| projection == self.uv.projection)
+                    .unwrap_or(0);
+                let step = if self.modifiers.shift() {
+                    projections.len() - 1
+                } else {
+                    1
+                };
+                self.uv.projection = projections[(current + step) % projections.len()];
+                println!("uv projection: {:?}", self.uv.projection);
+            }
+            Some(Self::NEXT_UV_PLANAR_AXIS_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.uv.planar_axis = self.uv.planar_axis.next();
+                println!("uv planar axis: {:?}", self.uv.planar_axis);
+            }
+            Some(Self::DECREASE_UV_SCALE_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.uv.scale /= Self::UV_SCALE_FACTOR;
+                println!("uv scale: {}", self.uv.scale);
+            }
+            Some(Self::INCREASE_UV_SCALE_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.uv.scale *= Self::UV_SCALE_FACTOR;
+                println!("uv scale: {}", self.uv.scale);
+            }
+            Some(Self::ROTATE_UV_CLOCKWISE_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.uv.rotation -= Self::UV_ROTATION_STEP;
+                println!("uv rotation: {:.0}°", self.uv.rotation.to_degrees());
+            }
+            Some(Self::ROTATE_UV_COUNTERCLOCKWISE_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.uv.rotation += Self::UV_ROTATION_STEP;
+                println!("uv rotation: {:.0}°", self.uv.rotation.to_degrees());
+            }
+            Some(
+                Self::NEXT_ENVIRONMENT_MODE_KEYCODE | Self::TOGGLE_ENVIRONMENT_FRESNEL_KEYCODE,
+            ) if matches!(input.state, ElementState::Pressed)
+                && matches!(self.material_mode, MaterialMode::Pbr) =>
+            {
+                println!(
+                    "the environment is only shown by the classic material, press {:?} to switch",
+                    Self::TOGGLE_MATERIAL_MODE_KEYCODE
+                );
+            }
+            Some(Self::NEXT_ENVIRONMENT_MODE_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.environment.mode = self.environment.mode.next();
+                println!("environment: {:?}", self.environment.mode);
+            }
+            Some(Self::TOGGLE_ENVIRONMENT_FRESNEL_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.environment.fresnel = !self.environment.fresnel;
+                println!("environment fresnel: {}", self.environment.fresnel);
+            }
+            Some(Self::TOGGLE_GAMMA_CORRECTION_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.gamma_correction = !self.gamma_correction;
+                println!("gamma correction: {}", self.gamma_correction);
+            }
+            Some(Self::DECREASE_NORMAL_STRENGTH_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.adjust_normal_strength(-Self::NORMAL_STRENGTH_STEP);
+            }
+            Some(Self::INCREASE_NORMAL_STRENGTH_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.adjust_normal_strength(Self::NORMAL_STRENGTH_STEP);
+            }
+            Some(Self::TOGGLE_MATERIAL_MODE_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.material_mode = match self.material_mode {
+                    MaterialMode::Classic => MaterialMode::Pbr,
+                    MaterialMode::Pbr => MaterialMode::Classic,
+                };
+                println!("material mode: {:?}", self.material_mode);
+            }
+            Some(Self::DECREASE_ROUGHNESS_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.adjust_pbr_material(-Self::PBR_PARAMETER_STEP, 0.0);
+            }
+            Some(Self::INCREASE_ROUGHNESS_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.adjust_pbr_material(Self::PBR_PARAMETER_STEP, 0.0);
+            }
+            Some(Self::DECREASE_METALLIC_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.adjust_pbr_material(0.0, -Self::PBR_PARAMETER_STEP);
+            }
+            Some(Self::INCREASE_METALLIC_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.adjust_pbr_material(0.0, Self::PBR_PARAMETER_STEP);
+            }
+            Some(Self::TOGGLE_ROTATION_INERTIA_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.camera.toggle_inertia();
+            }
+            Some(Self::RESET_CAMERA_TARGET_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.camera.reset_target();
+            }
+            Some(Self::TOGGLE_DIR_LIGHTS_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                let enabled = !self.lights.directional.any_enabled();
+                self.lights.directional.set_all(enabled);
+            }
+            Some(Self::TOGGLE_POINT_LIGHTS_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                let enabled = !self.lights.point.any_enabled();
+                self.lights.point.set_all(enabled);
+            }
+            Some(Self::TOGGLE_SPOT_LIGHTS_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                let enabled = !self.lights.spot.any_enabled();
+                self.lights.spot.set_all(enabled);
+            }
+            Some(Self::ADD_POINT_LIGHT_KEYCODE) if matches!(input.state, ElementState::Pressed) => {
+                if self.modifiers.shift() {
+                    self.remove_last_point_light();
+                } else {
+                    self.add_point_light();
+                }
+            }
+            Some(Self::TOGGLE_LIGHT_EDIT_MODE_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                self.light_editor.toggle(&self.lights);
+            }
+            Some(Self::TOGGLE_SHADOWS_KEYCODE) if matches!(input.state, ElementState::Pressed) => {
+                self.shadows.toggle();
+                println!("shadows: {}", self.shadows.is_enabled());
+            }
+            Some(Self::DECREASE_SHADOW_BIAS_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                println!("shadow bias: {:.6}", self.shadows.scale_bias(false));
+            }
+            Some(Self::INCREASE_SHADOW_BIAS_KEYCODE)
+                if matches!(input.state, ElementState::Pressed) =>
+            {
+                println!("shadow bias: {:.6}", self.shadows.scale_bias(true));
+            }
+            Some(keycode) if matches!(input.state, ElementState::Pressed) => {
+                if let Some(slot) = Self::bookmark_slot_of(keycode) {
+                    self.use_bookmark(slot);
+                }
+            }
             _ => (),
         };
     }
 
+    fn modifiers_changed_hook(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+
     fn cursor_move_hook(&mut self, next_pos: PhysicalPosition<f64>) {
         if let Some(prev_pos) = self.last_cursor_pos {
             self.update_rotation_angles(prev_pos, next_pos);
+            self.update_camera_target(prev_pos, next_pos);
         }
 
         self.last_cursor_pos = Some(next_pos);
@@ -318,6 +678,16 @@ impl Renderer for RgzRenderer {
         self.cursor_left = true;
     }
 
+    /// Shows the active material preset, which the PBR program does not use.
+    fn title(&self) -> Option<String> {
+        let material = match self.material_mode {
+            MaterialMode::Classic => MATERIAL_PRESETS[self.material_preset].name,
+            MaterialMode::Pbr => "PBR",
+        };
+
+        Some(format!("{} - {material}", env!("CARGO_PKG_NAME")))
+    }
+
     fn draw(&mut self, width: Option<u32>, height: Option<u32>) {
         let Some(width) = width else {
             return;
@@ -326,78 +696,110 @@ impl Renderer for RgzRenderer {
         let Some(height) = height else {
             return;
         };
+        self.window_height = height;
+
+        let now = Instant::now();
+        let dt = self
+            .last_frame_instant
+            .map_or(0.0, |last| (now - last).as_secs_f32())
+            .min(Self::MAX_FRAME_TIME);
+        self.last_frame_instant = Some(now);
+        self.camera.update(dt);
+
+        if let Some(scene) = self.scene_watcher.poll(dt) {
+            match scene {
+                Ok(scene) => {
+                    self.apply_scene(scene);
+                    println!("reloaded {}", self.scene_watcher.path().display());
+                }
+                Err(err) => eprintln!("{err}, keeping the current scene"),
+            }
+        }
+        self.material_maps.poll(dt);
+        self.pbr_maps.poll(dt);
 
         let model_matrix = Mat4::IDENTITY;
-        let view_matrix = self.calc_look_at_matrix();
-        let projection_matrix = self.get_proj_matrix(width, height);
+        let view_matrix = self.camera.view_matrix();
+        let projection_matrix = self.camera.projection_matrix(width, height);
 
         unsafe {
             self.gl.Enable(gl::DEPTH_TEST);
+        }
+
+        if self.shadows.is_enabled() && self.show_model {
+            self.shadows.render(
+                model_matrix,
+                self.figure.radius,
+                self.lights
+                    .directional
+                    .enabled()
+                    .next()
+                    .map(|light| light.direction),
+                self.lights
+                    .point
+                    .enabled()
+                    .next()
+                    .map(|light| light.position),
+            );
+
+            unsafe {
+                self.gl.Viewport(0, 0, width as i32, height as i32);
+            }
+        }
+
+        unsafe {
             self.gl.ClearColor(0.0, 0.0, 0.0, 1.0);
             self.gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
         }
 
-        self.texture.bind();
+        self.skybox.draw(
+            self.camera.skybox_matrix(width, height),
+            self.gamma_correction,
+        );
+
         self.polygon_array.use_array();
 
-        self.polygon_program.use_program();
-        self.polygon_program
-            .set_uniform_vec3("uDirLight.direction", Self::DIR_LIGHT.direction.to_array());
-        self.polygon_program
-            .set_uniform_vec3("uDirLight.ambient", Self::DIR_LIGHT.ambient.to_array());
-        self.polygon_program
-            .set_uniform_vec3("uDirLight.diffuse", Self::DIR_LIGHT.diffuse.to_array());
-        self.polygon_program
-            .set_uniform_vec3("uDirLight.specular", Self::DIR_LIGHT.specular.to_array());
-
-        self.polygon_program.set_uniform_vec3(
-            "uPointLight.position",
-            Self::POINT_LIGHT.position.to_array(),
-        );
-        self.polygon_program
-            .set_uniform_vec3("uPointLight.ambient", Self::POINT_LIGHT.ambient.to_array());
-        self.polygon_program
-            .set_uniform_vec3("uPointLight.diffuse", Self::POINT_LIGHT.diffuse.to_array());
-        self.polygon_program.set_uniform_vec3(
-            "uPointLight.specular",
-            Self::POINT_LIGHT.specular.to_array(),
-        );
-        self.polygon_program
-            .set_uniform_f32("uPointLight.constant", Self::POINT_LIGHT.constant);
-        self.polygon_program
-            .set_uniform_f32("uPointLight.linear", Self::POINT_LIGHT.linear);
-        self.polygon_program
-            .set_uniform_f32("uPointLight.quadratic", Self::POINT_LIGHT.quadratic);
-
-        self.polygon_program
-            .set_uniform_vec3("uMaterial.colorAmbient", Self::MATERIAL.ambient.to_array());
-        self.polygon_program
-            .set_uniform_vec3("uMaterial.colorDiffuse", Self::MATERIAL.diffuse.to_array());
-        self.polygon_program.set_uniform_vec3(
-            "uMaterial.colorSpecular",
-            Self::MATERIAL.specular.to_array(),
-        );
-        self.polygon_program
-            .set_uniform_f32("uMaterial.shininess", Self::MATERIAL.shininess);
-        self.polygon_program.set_uniform_bool(
-            "uMaterial.useColor",
-            matches!(self.model_kind, ModelKind::Color),
-        );
+        let polygon_program = match self.material_mode {
+            MaterialMode::Classic => &self.polygon_program,
+            MaterialMode::Pbr => &self.pbr_program,
+        };
 
-        let view_pos = self.calc_view_pos();
-        self.polygon_program
-            .set_uniform_vec3("uViewPos", [view_pos.0, view_pos.1, view_pos.2]);
+        polygon_program.use_program();
+        texture::assign_sampler_units(polygon_program);
+        self.lights.upload(polygon_program, self.gamma_correction);
+        self.shadows.upload(polygon_program);
+        polygon_program.set_uniform_i32("uShadingModel", self.shading_model as i32);
+        polygon_program.set_uniform_bool("uGammaCorrection", self.gamma_correction);
+        self.uv.upload(polygon_program);
+        self.material_maps
+            .upload_normal_map(polygon_program, self.normal_mapping);
+
+        match self.material_mode {
+            MaterialMode::Classic => {
+                // The PBR program has no environment map.
+                let has_skybox = self.skybox.bind_environment(polygon_program);
+                self.material.upload(polygon_program, self.gamma_correction);
+                self.environment.upload(polygon_program, has_skybox);
+                self.material_maps.upload(
+                    polygon_program,
+                    matches!(self.model_kind, ModelKind::Texture),
+                    self.gamma_correction,
+                );
+            }
+            MaterialMode::Pbr => {
+                self.pbr_material.upload(polygon_program);
+                self.pbr_maps.upload(polygon_program);
+                polygon_program.set_uniform_vec3("uPbrAmbient", Self::PBR_AMBIENT.to_array());
+            }
+        }
 
-        self.polygon_program
-            .set_uniform_mat4("uViewMat", view_matrix.to_cols_array());
-        self.polygon_program
-            .set_uniform_mat4("uModelMat", model_matrix.to_cols_array());
-        self.polygon_program
-            .set_uniform_f32("uRadius", self.figure_radius);
-        self.polygon_program
-            .set_uniform_u32("uSlicesCount", self.model_slices_count);
-        self.polygon_program
-            .set_uniform_mat4("uProjectionMat", projection_matrix.to_cols_array());
+        polygon_program.set_uniform_vec3("uViewPos", self.camera.view_pos().to_array());
+
+        polygon_program.set_uniform_mat4("uViewMat", view_matrix.to_cols_array());
+        polygon_program.set_uniform_mat4("uModelMat", model_matrix.to_cols_array());
+        polygon_program.set_uniform_f32("uRadius", self.figure.radius);
+        polygon_program.set_uniform_u32("uSlicesCount", self.figure.model_slices);
+        polygon_program.set_uniform_mat4("uProjectionMat", projection_matrix.to_cols_array());
 
         unsafe {
             if self.show_model {
@@ -414,9 +816,9 @@ impl Renderer for RgzRenderer {
         self.mesh_program
             .set_uniform_mat4("uModelMat", model_matrix.to_cols_array());
         self.mesh_program
-            .set_uniform_f32("uRadius", self.figure_radius);
+            .set_uniform_f32("uRadius", self.figure.radius);
         self.mesh_program
-            .set_uniform_u32("uSlicesCount", self.mesh_slices_count);
+            .set_uniform_u32("uSlicesCount", self.figure.mesh_slices);
         self.mesh_program
             .set_uniform_mat4("uProjectionMat", projection_matrix.to_cols_array());
 
@@ -426,9 +828,15 @@ impl Renderer for RgzRenderer {
                     .DrawArrays(gl::POINTS, 0, self.mesh_array.len() as i32);
             }
         }
+
+        if self.light_editor.is_active() {
+            self.draw_light_markers(view_matrix, projection_matrix);
+        }
     }
 
     fn resize(&mut self, width: i32, height: i32) {
+        self.window_height = height as u32;
+
         unsafe {
             self.gl.Viewport(0, 0, width, height);
         }
@@ -436,73 +844,281 @@ impl Renderer for RgzRenderer {
 }
 
 impl RgzRenderer {
-    fn get_proj_matrix(&self, width: u32, height: u32) -> Mat4 {
-        match self.projection {
-            Projection::Perspective => Mat4::perspective_lh(
-                std::f32::consts::FRAC_PI_4,
-                width as f32 / height as f32,
-                0.1,
-                100.0,
-            ),
-            Projection::Axonometric => Mat4::orthographic_rh(
-                -(width as f32 / 700.0),
-                width as f32 / 700.0,
-                -(height as f32 / 700.0),
-                height as f32 / 700.0,
-                40.0,
-                -40.0,
-            ),
+    fn update_rotation_angles(
+        &mut self,
+        prev_pos: PhysicalPosition<f64>,
+        next_pos: PhysicalPosition<f64>,
+    ) {
+        if !self.left_mouse_btn_pressed || self.cursor_left {
+            return;
         }
-    }
-
-    fn calc_look_at_matrix(&self) -> Mat4 {
-        let (x, y, z) = self.calc_view_pos();
 
-        Mat4::look_at_lh(
-            Vec3::new(x, y, z),
-            Vec3::new(0.0, 0.0, 0.0),
-            Vec3::new(0.0, 1.0, 0.0),
-        )
-    }
+        let delta_x = -prev_pos.x + next_pos.x;
+        let delta_y = prev_pos.y - next_pos.y;
 
-    fn calc_view_pos(&self) -> (f32, f32, f32) {
-        let x =
-            self.camera_zoom * self.camera_polar_angle.cos() * self.camera_azimuthal_angle.sin();
-        let y = self.camera_zoom * self.camera_polar_angle.sin();
-        let z =
-            self.camera_zoom * self.camera_polar_angle.cos() * self.camera_azimuthal_angle.cos();
+        let (delta_polar_angle, delta_azimuthal_angle) =
+            self.input_config
+                .rotation_delta(delta_x, delta_y, self.window_height);
 
-        (x, y, z)
+        if self.light_editor.is_active() {
+            self.light_editor
+                .orbit(&mut self.lights, delta_polar_angle, delta_azimuthal_angle);
+        } else {
+            self.camera.rotate(delta_polar_angle, delta_azimuthal_angle);
+        }
     }
 
-    fn update_rotation_angles(
+    fn update_camera_target(
         &mut self,
         prev_pos: PhysicalPosition<f64>,
         next_pos: PhysicalPosition<f64>,
     ) {
-        if !self.left_mouse_btn_pressed || self.cursor_left {
+        if !self.right_mouse_btn_pressed || self.cursor_left || self.light_editor.is_active() {
             return;
         }
 
-        let delta_x = -prev_pos.x + next_pos.x;
-        let delta_y = prev_pos.y - next_pos.y;
+        let height = self.window_height.max(1) as f64;
+        self.camera.pan(
+            ((next_pos.x - prev_pos.x) / height) as f32,
+            ((next_pos.y - prev_pos.y) / height) as f32,
+        );
+    }
+
+    /// Handles the light-edit mode keys, returns `false` for keys it doesn't use.
+    fn light_edit_input(&mut self, keycode: VirtualKeyCode) -> bool {
+        let step = Self::LIGHT_MOVE_STEP;
+        let offset = match keycode {
+            VirtualKeyCode::Left => Vec3::new(-step, 0.0, 0.0),
+            VirtualKeyCode::Right => Vec3::new(step, 0.0, 0.0),
+            VirtualKeyCode::Up => Vec3::new(0.0, 0.0, step),
+            VirtualKeyCode::Down => Vec3::new(0.0, 0.0, -step),
+            VirtualKeyCode::PageUp => Vec3::new(0.0, step, 0.0),
+            VirtualKeyCode::PageDown => Vec3::new(0.0, -step, 0.0),
+            Self::SELECT_NEXT_LIGHT_KEYCODE => {
+                self.light_editor.select_next(&self.lights);
+                return true;
+            }
+            Self::CYCLE_LIGHT_COLOR_KEYCODE => {
+                self.light_editor.cycle_color(&mut self.lights);
+                return true;
+            }
+            Self::TOGGLE_SELECTED_LIGHT_KEYCODE => {
+                self.light_editor.toggle_selected(&mut self.lights);
+                return true;
+            }
+            Self::WEAKEN_ATTENUATION_KEYCODE => {
+                self.light_editor.scale_attenuation(&mut self.lights, false);
+                return true;
+            }
+            Self::STRENGTHEN_ATTENUATION_KEYCODE => {
+                self.light_editor.scale_attenuation(&mut self.lights, true);
+                return true;
+            }
+            _ => return false,
+        };
+
+        self.light_editor.translate(&mut self.lights, offset);
+        true
+    }
+
+    fn draw_light_markers(&self, view_matrix: Mat4, projection_matrix: Mat4) {
+        self.marker_array.use_array();
+        self.marker_elements.use_array();
+
+        self.marker_program.use_program();
+        self.marker_program
+            .set_uniform_mat4("uViewMat", view_matrix.to_cols_array());
+        self.marker_program
+            .set_uniform_mat4("uProjectionMat", projection_matrix.to_cols_array());
+
+        for marker in self.light_editor.markers(&self.lights) {
+            let scale = if marker.selected {
+                Self::SELECTED_MARKER_SCALE
+            } else {
+                Self::MARKER_SCALE
+            };
+            let model_matrix = Mat4::from_scale_rotation_translation(
+                Vec3::splat(scale),
+                Quat::IDENTITY,
+                marker.position,
+            );
+
+            self.marker_program
+                .set_uniform_mat4("uModelMat", model_matrix.to_cols_array());
+            self.marker_program
+                .set_uniform_vec3("uColor", marker.color.to_array());
+
+            unsafe {
+                self.gl.DrawElements(
+                    gl::TRIANGLES,
+                    self.marker_elements.len() as i32,
+                    gl::UNSIGNED_SHORT,
+                    std::ptr::null(),
+                );
+            }
+        }
+    }
+
+    fn bookmark_slot_of(keycode: VirtualKeyCode) -> Option<u8> {
+        Self::BOOKMARK_SLOT_KEYCODES
+            .iter()
+            .position(|&slot_keycode| slot_keycode == keycode)
+            .map(|index| index as u8 + 1)
+    }
+
+    fn use_bookmark(&mut self, slot: u8) {
+        if self.modifiers.shift() {
+            match self.bookmarks.store(slot, self.camera.view()) {
+                Ok(bookmark) => println!("stored camera bookmark \"{}\"", bookmark.name),
+                Err(err) => eprintln!("{err}"),
+            }
+
+            return;
+        }
+
+        match self.bookmarks.get(slot) {
+            Some(bookmark) => {
+                println!("recalled camera bookmark \"{}\"", bookmark.name);
+                self.camera.go_to(bookmark.view);
+            }
+            None => eprintln!("camera bookmark slot {slot} is empty"),
+        }
+    }
+
+    /// Vertex array with one point per `(polar, azimuth)` index pair of a figure cut into
+    /// `slices_count` slices both ways.
+    fn slices_array(gl: &gl::Gl, slices_count: u32, program: &ShaderProgram) -> VerticesArray {
+        let mut angles = Vec::new();
+
+        for polar_index in 0..slices_count {
+            for azimuth_index in 0..slices_count {
+                angles.push(polar_index);
+                angles.push(azimuth_index);
+            }
+        }
+
+        let array = VerticesArray::new(gl.clone(), angles);
+
+        array.set_attrib_int_pointer(
+            program.attrib_location_of("iPolarAngleIndex"),
+            AttribPointer {
+                size: Size::One,
+                stride: 2 * std::mem::size_of::<u32>(),
+                offset: 0,
+                ty: gl::UNSIGNED_INT,
+            },
+        );
+        array.set_attrib_int_pointer(
+            program.attrib_location_of("iAzimuthAngleIndex"),
+            AttribPointer {
+                size: Size::One,
+                stride: 2 * std::mem::size_of::<u32>(),
+                offset: std::mem::size_of::<u32>(),
+                ty: gl::UNSIGNED_INT,
+            },
+        );
+
+        array
+    }
 
-        let delta_polar_angle = delta_y as f32 * Self::DELTA_Y_INTO_DELTA_ANGLE_FACTOR;
-        let delta_azimuthal_angle = delta_x as f32 * Self::DELTA_X_INTO_DELTA_ANGLE_FACTOR;
+    /// Replaces every scene-controlled parameter, rebuilding the vertex arrays only when the
+    /// slice counts change.
+    fn apply_scene(&mut self, scene: Scene) {
+        if scene.figure.model_slices != self.figure.model_slices {
+            self.polygon_array =
+                Self::slices_array(&self.gl, scene.figure.model_slices, &self.polygon_program);
+        }
+        if scene.figure.mesh_slices != self.figure.mesh_slices {
+            self.mesh_array =
+                Self::slices_array(&self.gl, scene.figure.mesh_slices, &self.mesh_program);
+        }
+        self.figure = scene.figure;
 
-        let polar_angle = self.camera_polar_angle + delta_polar_angle;
-        let polar_angle = if polar_angle > Self::POLAR_ANGLE_MAX {
-            Self::POLAR_ANGLE_MAX
-        } else if polar_angle < Self::POLAR_ANGLE_MIN {
-            Self::POLAR_ANGLE_MIN
+        self.camera
+            .set_zoom_limits(scene.camera.zoom_min, scene.camera.zoom_max);
+        self.camera.set_projection_config(scene.camera.projection);
+        self.input_config = scene.input;
+
+        // The preset name has been validated while loading.
+        if let Some((preset, material)) = scene.material.resolve() {
+            self.material_preset = preset;
+            self.material = material;
+        }
+        let mut maps = scene.material.maps;
+        // The texture given on the command line takes precedence over the scene file.
+        if let Some(texture) = &Args::get().texture {
+            maps.diffuse = Some(texture.clone());
+        }
+        self.material_maps = maps.load(&self.gl);
+        self.environment = scene.material.environment;
+        self.uv = scene.uv;
+        self.pbr_material = scene.pbr;
+        self.lights = scene.lights.to_lights();
+        self.skybox.configure(&scene.skybox);
+    }
+
+    fn cycle_procedural_texture(&mut self) {
+        // `None` stands for the loaded diffuse map and sits between the last and first texture.
+        let mut choices = vec![None];
+        choices.extend(ProceduralTexture::ALL.map(Some));
+
+        let current = choices
+            .iter()
+            .position(|&choice| choice == self.material_maps.procedural_kind())
+            .unwrap_or(0);
+        let step = if self.modifiers.shift() {
+            choices.len() - 1
         } else {
-            polar_angle
+            1
         };
+        let next = choices[(current + step) % choices.len()];
 
-        let azimuthal_angle =
-            (self.camera_azimuthal_angle + delta_azimuthal_angle) % (std::f32::consts::PI * 2.0);
+        self.material_maps.set_procedural(&self.gl, next);
+        match next {
+            Some(kind) => println!("diffuse map: procedural {kind:?}"),
+            None => println!("diffuse map: loaded texture"),
+        }
+    }
 
-        self.camera_polar_angle = polar_angle;
-        self.camera_azimuthal_angle = azimuthal_angle;
+    fn adjust_normal_strength(&mut self, delta: f32) {
+        let strength = &mut self.material_maps.normal_strength;
+
+        *strength = (*strength + delta).max(0.0);
+        println!("normal map strength: {strength:.2}");
+    }
+
+    fn adjust_pbr_material(&mut self, delta_roughness: f32, delta_metallic: f32) {
+        let material = &mut self.pbr_material;
+
+        material.roughness =
+            (material.roughness + delta_roughness).clamp(PbrMaterial::ROUGHNESS_MIN, 1.0);
+        material.metallic = (material.metallic + delta_metallic).clamp(0.0, 1.0);
+        println!(
+            "pbr material: roughness {:.2}, metallic {:.2}",
+            material.roughness, material.metallic
+        );
+    }
+
+    fn add_point_light(&mut self) {
+        let index = self.lights.point.len();
+        // Golden angle steps spread any number of lights evenly around the figure.
+        let angle = index as f32 * 2.399_963;
+        let color = Self::EXTRA_POINT_LIGHT_COLORS[index % Self::EXTRA_POINT_LIGHT_COLORS.len()];
+
+        let light = PointLight {
+            position: Vec3::new(2.0 * angle.cos(), 1.0, 2.0 * angle.sin()),
+            diffuse: color,
+            ..PointLight::default()
+        };
+
+        if self.lights.point.add(light).is_none() {
+            eprintln!("can't add more than {index} point lights");
+        }
+    }
+
+    fn remove_last_point_light(&mut self) {
+        if let Some(index) = self.lights.point.len().checked_sub(1) {
+            self.lights.point.remove(index);
+        }
     }
 }
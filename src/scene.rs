@@ -0,0 +1,601 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use glam::{Vec2, Vec3};
+use serde::Deserialize;
+
+use crate::{
+    camera::Camera,
+    error::SceneError,
+    input::InputConfig,
+    light::{
+        DirLight, Lights, PointLight, SpotLight, MAX_DIR_LIGHTS, MAX_POINT_LIGHTS, MAX_SPOT_LIGHTS,
+    },
+    material::{
+        open_map, Environment, Material, MaterialMaps, MaterialPreset, PbrMaterial,
+        MATERIAL_PRESETS,
+    },
+    procedural::{self, ProceduralTexture},
+    projection::ProjectionConfig,
+    skybox::SkyboxConfig,
+    texture::{ColorSpace, MagFilter, Texture, TextureOptions},
+    uv::UvMapping,
+    watcher::FileWatcher,
+};
+
+/// Everything about the figure, its material and its lighting that can be tweaked without
+/// recompiling, read from a TOML file. Missing keys keep their defaults.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Scene {
+    pub figure: FigureConfig,
+    pub camera: CameraConfig,
+    pub input: InputConfig,
+    pub material: MaterialConfig,
+    pub pbr: PbrMaterial,
+    pub lights: LightsConfig,
+    pub skybox: SkyboxConfig,
+    pub uv: UvMapping,
+}
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct FigureConfig {
+    pub radius: f32,
+    pub model_slices: u32,
+    pub mesh_slices: u32,
+}
+
+impl Default for FigureConfig {
+    fn default() -> Self {
+        Self {
+            radius: 1.0,
+            model_slices: 1000,
+            mesh_slices: 100,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct CameraConfig {
+    pub zoom_min: f32,
+    pub zoom_max: f32,
+    pub projection: ProjectionConfig,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            zoom_min: Camera::ZOOM_MIN,
+            zoom_max: Camera::ZOOM_MAX,
+            projection: ProjectionConfig::default(),
+        }
+    }
+}
+
+/// Classic material given by a preset name, any of the other keys override the preset values.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct MaterialConfig {
+    pub preset: String,
+    pub ambient: Option<Vec3>,
+    pub diffuse: Option<Vec3>,
+    pub specular: Option<Vec3>,
+    pub shininess: Option<f32>,
+    pub maps: MaterialMapsConfig,
+    pub environment: Environment,
+}
+
+impl MaterialConfig {
+    /// Index of the preset in [`MATERIAL_PRESETS`] along with the material after the overrides.
+    pub fn resolve(&self) -> Option<(usize, Material)> {
+        let index = MaterialPreset::find(&self.preset)?;
+        let preset = MATERIAL_PRESETS[index].material;
+
+        let material = Material {
+            ambient: self.ambient.unwrap_or(preset.ambient),
+            diffuse: self.diffuse.unwrap_or(preset.diffuse),
+            specular: self.specular.unwrap_or(preset.specular),
+            shininess: self.shininess.unwrap_or(preset.shininess),
+        };
+
+        Some((index, material))
+    }
+}
+
+impl Default for MaterialConfig {
+    fn default() -> Self {
+        Self {
+            preset: "ruby".to_owned(),
+            ambient: None,
+            diffuse: None,
+            specular: None,
+            shininess: None,
+            maps: MaterialMapsConfig::default(),
+            environment: Environment::default(),
+        }
+    }
+}
+
+/// Texture maps of the classic material, see [`MaterialMaps`].
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct MaterialMapsConfig {
+    pub diffuse: Option<PathBuf>,
+    /// Generated texture replacing the diffuse map.
+    pub procedural: Option<ProceduralTexture>,
+    pub specular: Option<PathBuf>,
+    pub emissive: Option<PathBuf>,
+    pub tint: Vec3,
+    /// Generated bumps are used when no normal map is given.
+    pub normal: Option<PathBuf>,
+    pub normal_strength: f32,
+    /// Sampling of all the maps.
+    pub sampling: TextureOptions,
+}
+
+impl MaterialMapsConfig {
+    const DEFAULT_DIFFUSE_MAP: &'static str = "texture.jpg";
+
+    /// Opens the maps, a diffuse map that fails to load is replaced by a checkerboard. The
+    /// placeholders keep watching the configured file and are replaced once it loads.
+    pub fn load(&self, gl: &gl::Gl) -> MaterialMaps {
+        let open = |path: &Option<PathBuf>, color_space| {
+            path.as_deref()
+                .and_then(|path| open_map(gl, path, color_space, &self.sampling))
+        };
+        let placeholder = |path: &Option<PathBuf>, img, color_space, options: &TextureOptions| {
+            let texture = Texture::from_image(gl.clone(), &img, color_space, options);
+            match path {
+                Some(path) => texture.watching(path, color_space, &self.sampling),
+                None => texture,
+            }
+        };
+
+        let diffuse = open(&self.diffuse, ColorSpace::Srgb).or_else(|| {
+            // Nearest magnification keeps the cell edges sharp up close.
+            let options = self.sampling.mag_filter(MagFilter::Nearest);
+            self.diffuse.as_ref().map(|_| {
+                placeholder(
+                    &self.diffuse,
+                    procedural::checkerboard(512, 16),
+                    ColorSpace::Srgb,
+                    &options,
+                )
+            })
+        });
+
+        let mut maps = MaterialMaps {
+            diffuse,
+            procedural: None,
+            options: self.sampling,
+            specular: open(&self.specular, ColorSpace::Linear),
+            emissive: open(&self.emissive, ColorSpace::Srgb),
+            tint: self.tint,
+            normal: open(&self.normal, ColorSpace::Linear).or_else(|| {
+                Some(placeholder(
+                    &self.normal,
+                    procedural::bumps_normal_map(),
+                    ColorSpace::Linear,
+                    &self.sampling,
+                ))
+            }),
+            normal_strength: self.normal_strength,
+        };
+
+        maps.set_procedural(gl, self.procedural);
+        maps
+    }
+}
+
+impl Default for MaterialMapsConfig {
+    fn default() -> Self {
+        Self {
+            diffuse: Some(Self::DEFAULT_DIFFUSE_MAP.into()),
+            procedural: None,
+            specular: None,
+            emissive: None,
+            tint: Vec3::ONE,
+            normal: None,
+            normal_strength: 1.0,
+            sampling: TextureOptions::default(),
+        }
+    }
+}
+
+/// Without a `[lights]` table there is one light of each kind, once it's present only the
+/// listed lights exist.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LightsConfig {
+    #[serde(default)]
+    pub directional: Vec<DirLight>,
+    #[serde(default)]
+    pub point: Vec<PointLight>,
+    #[serde(default)]
+    pub spot: Vec<SpotLight>,
+}
+
+impl LightsConfig {
+    pub fn to_lights(&self) -> Lights {
+        let mut lights = Lights::default();
+
+        for &light in &self.directional {
+            lights.directional.add(light);
+        }
+        for &light in &self.point {
+            lights.point.add(light);
+        }
+        for &light in &self.spot {
+            lights.spot.add(light);
+        }
+
+        lights
+    }
+}
+
+impl Default for LightsConfig {
+    fn default() -> Self {
+        Self {
+            directional: vec![DirLight::default()],
+            point: vec![PointLight::default()],
+            spot: vec![SpotLight::default()],
+        }
+    }
+}
+
+impl Scene {
+    pub const FILE_NAME: &'static str = "scene.toml";
+
+    const SLICES_MIN: u32 = 2;
+    const SLICES_MAX: u32 = 4096;
+
+    /// Loads and validates the scene at `path`, a missing file gives the default scene.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SceneError> {
+        let scene = match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str::<Self>(&content).map_err(SceneError::Parse)?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Self::default(),
+            Err(err) => return Err(SceneError::Io(err)),
+        };
+
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    fn validate(&self) -> Result<(), SceneError> {
+        let mut problems = Problems::default();
+
+        let figure = &self.figure;
+        problems.positive("figure.radius", figure.radius);
+        for (key, slices) in [
+            ("figure.model_slices", figure.model_slices),
+            ("figure.mesh_slices", figure.mesh_slices),
+        ] {
+            problems.check(
+                key,
+                (Self::SLICES_MIN..=Self::SLICES_MAX).contains(&slices),
+                format!(
+                    "must be between {} and {}",
+                    Self::SLICES_MIN,
+                    Self::SLICES_MAX
+                ),
+            );
+        }
+
+        let camera = &self.camera;
+        problems.check(
+            "camera.zoom_max",
+            camera.zoom_max < 0.0,
+            "must be negative, the camera looks at the target from a distance",
+        );
+        problems.check(
+            "camera.zoom_min",
+            camera.zoom_min < camera.zoom_max,
+            "must be less than camera.zoom_max",
+        );
+        let projection = &camera.projection;
+        problems.check(
+            "camera.projection.fov_y",
+            projection.fov_y > 0.0 && projection.fov_y < std::f32::consts::PI,
+            "must be between 0 and PI radians",
+        );
+        problems.positive("camera.projection.near", projection.near);
+        problems.check(
+            "camera.projection.far",
+            projection.far > projection.near,
+            "must be greater than camera.projection.near",
+        );
+
+        problems.positive(
+            "input.rotation_sensitivity",
+            self.input.rotation_sensitivity,
+        );
+        problems.positive("input.zoom_sensitivity", self.input.zoom_sensitivity);
+        problems.positive("input.pixels_per_line", self.input.pixels_per_line);
+
+        let material = &self.material;
+        problems.check(
+            "material.preset",
+            MaterialPreset::find(&material.preset).is_some(),
+            format!("unknown preset \"{}\"", material.preset),
+        );
+        for (key, color) in [
+            ("material.ambient", material.ambient),
+            ("material.diffuse", material.diffuse),
+            ("material.specular", material.specular),
+        ] {
+            if let Some(color) = color {
+                problems.color(key, color);
+            }
+        }
+        if let Some(shininess) = material.shininess {
+            problems.positive("material.shininess", shininess);
+        }
+        problems.color("material.maps.tint", material.maps.tint);
+        problems.non_negative(
+            "material.maps.normal_strength",
+            material.maps.normal_strength,
+        );
+        problems.check(
+            "material.environment.ior",
+            material.environment.ior >= 1.0,
+            "must be at least 1",
+        );
+        let sampling = &material.maps.sampling;
+        problems.check(
+            "material.maps.sampling.anisotropy",
+            sampling.anisotropy >= 1.0,
+            "must be at least 1",
+        );
+
+        let pbr = &self.pbr;
+        problems.unit_interval("pbr.metallic", pbr.metallic);
+        problems.unit_interval("pbr.roughness", pbr.roughness);
+        problems.unit_interval("pbr.ao", pbr.ao);
+        problems.check(
+            "pbr.albedo",
+            pbr.albedo.cmpge(Vec3::ZERO).all() && pbr.albedo.cmple(Vec3::ONE).all(),
+            "components must be between 0 and 1",
+        );
+
+        self.lights.validate(&mut problems);
+
+        problems.check(
+            "uv.scale",
+            self.uv.scale.cmpne(Vec2::ZERO).all(),
+            "components must not be zero",
+        );
+
+        problems.check(
+            "skybox",
+            self.skybox.equirectangular.is_none() || self.skybox.faces.is_none(),
+            "equirectangular and faces are mutually exclusive",
+        );
+
+        if problems.0.is_empty() {
+            Ok(())
+        } else {
+            Err(SceneError::Invalid(problems.0))
+        }
+    }
+}
+
+impl LightsConfig {
+    fn validate(&self, problems: &mut Problems) {
+        problems.count("lights.directional", self.directional.len(), MAX_DIR_LIGHTS);
+        problems.count("lights.point", self.point.len(), MAX_POINT_LIGHTS);
+        problems.count("lights.spot", self.spot.len(), MAX_SPOT_LIGHTS);
+
+        for (index, light) in self.directional.iter().enumerate() {
+            let key = format!("lights.directional[{index}]");
+
+            problems.non_zero(&format!("{key}.direction"), light.direction);
+            problems.non_negative(&format!("{key}.intensity"), light.intensity);
+            problems.color(&format!("{key}.ambient"), light.ambient);
+            problems.color(&format!("{key}.diffuse"), light.diffuse);
+            problems.color(&format!("{key}.specular"), light.specular);
+        }
+
+        for (index, light) in self.point.iter().enumerate() {
+            let key = format!("lights.point[{index}]");
+
+            problems.non_negative(&format!("{key}.intensity"), light.intensity);
+            problems.color(&format!("{key}.ambient"), light.ambient);
+            problems.color(&format!("{key}.diffuse"), light.diffuse);
+            problems.color(&format!("{key}.specular"), light.specular);
+            problems.attenuation(&key, light.constant, light.linear, light.quadratic);
+        }
+
+        for (index, light) in self.spot.iter().enumerate() {
+            let key = format!("lights.spot[{index}]");
+
+            problems.non_zero(&format!("{key}.direction"), light.direction);
+            problems.check(
+                &format!("{key}.inner_cutoff"),
+                light.inner_cutoff > 0.0 && light.inner_cutoff < light.outer_cutoff,
+                "must be positive and less than outer_cutoff",
+            );
+            problems.check(
+                &format!("{key}.outer_cutoff"),
+                light.outer_cutoff < std::f32::consts::FRAC_PI_2,
+                "must be less than PI / 2 radians",
+            );
+            problems.non_negative(&format!("{key}.intensity"), light.intensity);
+            problems.color(&format!("{key}.ambient"), light.ambient);
+            problems.color(&format!("{key}.diffuse"), light.diffuse);
+            problems.color(&format!("{key}.specular"), light.specular);
+            problems.attenuation(&key, light.constant, light.linear, light.quadratic);
+        }
+    }
+}
+
+/// Validation problems collected so they can all be reported at once.
+#[derive(Default)]
+struct Problems(Vec<String>);
+
+impl Problems {
+    fn check(&mut self, key: &str, ok: bool, problem: impl std::fmt::Display) {
+        if !ok {
+            self.0.push(format!("{key}: {problem}"));
+        }
+    }
+
+    fn positive(&mut self, key: &str, value: f32) {
+        self.check(key, value > 0.0, "must be positive");
+    }
+
+    fn non_negative(&mut self, key: &str, value: f32) {
+        self.check(key, value >= 0.0, "must not be negative");
+    }
+
+    fn unit_interval(&mut self, key: &str, value: f32) {
+        self.check(key, (0.0..=1.0).contains(&value), "must be between 0 and 1");
+    }
+
+    fn color(&mut self, key: &str, color: Vec3) {
+        self.check(
+            key,
+            color.cmpge(Vec3::ZERO).all(),
+            "components must not be negative",
+        );
+    }
+
+    fn non_zero(&mut self, key: &str, vector: Vec3) {
+        self.check(key, vector.length_squared() > 0.0, "must not be zero");
+    }
+
+    fn count(&mut self, key: &str, count: usize, max: usize) {
+        self.check(
+            key,
+            count <= max,
+            format!("at most {max} lights are supported"),
+        );
+    }
+
+    fn attenuation(&mut self, key: &str, constant: f32, linear: f32, quadratic: f32) {
+        self.non_negative(&format!("{key}.constant"), constant);
+        self.non_negative(&format!("{key}.linear"), linear);
+        self.non_negative(&format!("{key}.quadratic"), quadratic);
+        self.check(
+            key,
+            constant + linear + quadratic > 0.0,
+            "attenuation terms must not all be zero",
+        );
+    }
+}
+
+/// Reloads the scene file when it changes so changes can be applied while running.
+pub(crate) struct SceneWatcher(FileWatcher);
+
+impl SceneWatcher {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self(FileWatcher::new(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    /// Reloads the scene once the file has changed since the last call, `dt` is the time in
+    /// seconds since the last call.
+    pub fn poll(&mut self, dt: f32) -> Option<Result<Scene, SceneError>> {
+        self.0.poll(dt).then(|| Scene::load(self.0.path()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FigureConfig, Scene};
+    use crate::{error::SceneError, light::MAX_SPOT_LIGHTS};
+
+    /// Parses and validates a scene like [`Scene::load`] does.
+    fn parse(content: &str) -> Result<Scene, SceneError> {
+        let scene = toml::from_str::<Scene>(content).map_err(SceneError::Parse)?;
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    fn problems(content: &str) -> Vec<String> {
+        match parse(content) {
+            Err(SceneError::Invalid(problems)) => problems,
+            Err(err) => panic!("expected validation problems, got {err}"),
+            Ok(_) => panic!("expected validation problems, the scene is valid"),
+        }
+    }
+
+    #[test]
+    fn sample_scene_is_valid_and_default() {
+        let scene = parse(include_str!("../scene.toml")).unwrap();
+
+        assert!(scene.figure == FigureConfig::default());
+        assert_eq!(scene.material.preset, "ruby");
+        assert_eq!(scene.lights.to_lights().spot.len(), 1);
+    }
+
+    #[test]
+    fn empty_scene_is_valid() {
+        assert!(parse("").is_ok());
+    }
+
+    #[test]
+    fn out_of_range_values_are_all_reported() {
+        let problems = problems(
+            r#"
+            [figure]
+            radius = -1.0
+            model_slices = 1
+
+            [material]
+            preset = "plastic"
+
+            [pbr]
+            roughness = 1.5
+
+            [[lights.spot]]
+            inner_cutoff = 0.3
+            outer_cutoff = 0.3
+            "#,
+        );
+
+        assert_eq!(
+            problems,
+            [
+                "figure.radius: must be positive",
+                "figure.model_slices: must be between 2 and 4096",
+                "material.preset: unknown preset \"plastic\"",
+                "pbr.roughness: must be between 0 and 1",
+                "lights.spot[0].inner_cutoff: must be positive and less than outer_cutoff",
+            ]
+        );
+    }
+
+    #[test]
+    fn too_many_lights_are_reported() {
+        let spot = "[[lights.spot]]\n".repeat(MAX_SPOT_LIGHTS + 1);
+
+        assert_eq!(
+            problems(&spot),
+            [format!(
+                "lights.spot: at most {MAX_SPOT_LIGHTS} lights are supported"
+            )]
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        for content in [
+            "[figure]\nradus = 1.0",
+            "[camera.projection]\nfov = 1.0",
+            "[[lights.point]]\ncolor = [1.0, 1.0, 1.0]",
+            "[shadows]",
+        ] {
+            assert!(
+                matches!(parse(content), Err(SceneError::Parse(_))),
+                "{content:?} was accepted"
+            );
+        }
+    }
+}
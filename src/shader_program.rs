@@ -1,4 +1,4 @@
-use std::ffi::CString;
+use std::{cell::RefCell, collections::HashMap, ffi::CString};
 
 use crate::{array::AttribLocation, error::GlError, ShaderKind};
 
@@ -6,6 +6,7 @@ pub(crate) struct ShaderProgramBuilder<V, G, F> {
     vertex_shader: V,
     geometry_shader: G,
     fragment_shader: F,
+    includes: Vec<(String, Vec<u8>)>,
     gl: gl::Gl,
 }
 
@@ -17,11 +18,46 @@ impl ShaderProgramBuilder<None, None, None> {
             vertex_shader: None,
             geometry_shader: None,
             fragment_shader: None,
+            includes: Vec::new(),
             gl,
         }
     }
 }
 
+impl<V, G, F> ShaderProgramBuilder<V, G, F> {
+    /// Makes `#include "<name>"` lines in the shaders expand to `source_code`.
+    pub fn include(mut self, name: &str, source_code: &[u8]) -> Self {
+        self.includes
+            .push((name.to_owned(), Vec::from(source_code)));
+        self
+    }
+
+    fn resolve_includes(&self, source_code: &[u8]) -> Vec<u8> {
+        let mut resolved = Vec::with_capacity(source_code.len());
+
+        for line in source_code.split_inclusive(|&byte| byte == b'\n') {
+            let include = line
+                .trim_ascii()
+                .strip_prefix(b"#include \"")
+                .and_then(|name| name.strip_suffix(b"\""))
+                .and_then(|name| {
+                    self.includes
+                        .iter()
+                        .find(|(include_name, _)| include_name.as_bytes() == name)
+                });
+
+            if let Some((_, include_source_code)) = include {
+                resolved.extend_from_slice(include_source_code);
+                resolved.push(b'\n');
+            } else {
+                resolved.extend_from_slice(line);
+            }
+        }
+
+        resolved
+    }
+}
+
 impl<G, F> ShaderProgramBuilder<None, G, F> {
     pub fn vertex_shader(self, source_code: &[u8]) -> ShaderProgramBuilder<Vec<u8>, G, F> {
         let mut source_code = Vec::from(source_code);
@@ -33,6 +69,7 @@ impl<G, F> ShaderProgramBuilder<None, G, F> {
             vertex_shader: source_code,
             geometry_shader: self.geometry_shader,
             fragment_shader: self.fragment_shader,
+            includes: self.includes,
             gl: self.gl,
         }
     }
@@ -49,6 +86,7 @@ impl<V, F> ShaderProgramBuilder<V, None, F> {
             vertex_shader: self.vertex_shader,
             geometry_shader: source_code,
             fragment_shader: self.fragment_shader,
+            includes: self.includes,
             gl: self.gl,
         }
     }
@@ -65,6 +103,7 @@ impl<V, G> ShaderProgramBuilder<V, G, None> {
             vertex_shader: self.vertex_shader,
             geometry_shader: self.geometry_shader,
             fragment_shader: source_code,
+            includes: self.includes,
             gl: self.gl,
         }
     }
@@ -73,10 +112,16 @@ impl<V, G> ShaderProgramBuilder<V, G, None> {
 impl ShaderProgramBuilder<Vec<u8>, None, Vec<u8>> {
     pub fn build(self) -> Result<ShaderProgram, GlError> {
         unsafe {
-            let vertex_shader_id =
-                compile_shader(&self.gl, &self.vertex_shader, ShaderKind::Vertex)?;
-            let fragment_shader_id =
-                compile_shader(&self.gl, &self.fragment_shader, ShaderKind::Fragment)?;
+            let vertex_shader_id = compile_shader(
+                &self.gl,
+                &self.resolve_includes(&self.vertex_shader),
+                ShaderKind::Vertex,
+            )?;
+            let fragment_shader_id = compile_shader(
+                &self.gl,
+                &self.resolve_includes(&self.fragment_shader),
+                ShaderKind::Fragment,
+            )?;
 
             let program_id = self.gl.CreateProgram();
             self.gl.AttachShader(program_id, vertex_shader_id);
@@ -91,6 +136,7 @@ impl ShaderProgramBuilder<Vec<u8>, None, Vec<u8>> {
             Ok(ShaderProgram {
                 gl: self.gl,
                 id: program_id,
+                uniform_locations: RefCell::default(),
             })
         }
     }
@@ -99,12 +145,21 @@ impl ShaderProgramBuilder<Vec<u8>, None, Vec<u8>> {
 impl ShaderProgramBuilder<Vec<u8>, Vec<u8>, Vec<u8>> {
     pub fn build(self) -> Result<ShaderProgram, GlError> {
         unsafe {
-            let vertex_shader_id =
-                compile_shader(&self.gl, &self.vertex_shader, ShaderKind::Vertex)?;
-            let geometry_shader_id =
-                compile_shader(&self.gl, &self.geometry_shader, ShaderKind::Geometry)?;
-            let fragment_shader_id =
-                compile_shader(&self.gl, &self.fragment_shader, ShaderKind::Fragment)?;
+            let vertex_shader_id = compile_shader(
+                &self.gl,
+                &self.resolve_includes(&self.vertex_shader),
+                ShaderKind::Vertex,
+            )?;
+            let geometry_shader_id = compile_shader(
+                &self.gl,
+                &self.resolve_includes(&self.geometry_shader),
+                ShaderKind::Geometry,
+            )?;
+            let fragment_shader_id = compile_shader(
+                &self.gl,
+                &self.resolve_includes(&self.fragment_shader),
+                ShaderKind::Fragment,
+            )?;
 
             let program_id = self.gl.CreateProgram();
             self.gl.AttachShader(program_id, vertex_shader_id);
@@ -121,6 +176,7 @@ impl ShaderProgramBuilder<Vec<u8>, Vec<u8>, Vec<u8>> {
             Ok(ShaderProgram {
                 gl: self.gl,
                 id: program_id,
+                uniform_locations: RefCell::default(),
             })
         }
     }
@@ -203,6 +259,8 @@ pub unsafe fn get_log(gl: &gl::Gl, id: gl::types::GLuint) -> String {
 pub(crate) struct ShaderProgram {
     gl: gl::Gl,
     id: gl::types::GLuint,
+    /// Uniform locations by name, looked up on first use. Missing uniforms are cached as -1.
+    uniform_locations: RefCell<HashMap<String, gl::types::GLint>>,
 }
 
 impl ShaderProgram {
@@ -212,6 +270,7 @@ impl ShaderProgram {
         }
     }
 
+    #[allow(dead_code)]
     pub fn id(&self) -> gl::types::GLuint {
         self.id
     }
@@ -223,100 +282,94 @@ impl ShaderProgram {
         AttribLocation::new(id as gl::types::GLuint)
     }
 
-    pub fn set_uniform_bool(&self, name: &str, value: bool) {
-        let name = CString::new(name).unwrap();
+    /// Whether the program has an active `name` uniform, without the warning of the setters.
+    pub fn has_uniform(&self, name: &str) -> bool {
+        self.find_uniform(name, false) != -1
+    }
 
-        unsafe {
-            self.gl.ProgramUniform1i(
-                self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
-                value.into(),
+    /// Location of the `name` uniform, -1 when the program has no such active uniform. Debug
+    /// builds warn the first time a missing name is looked up.
+    fn uniform_location(&self, name: &str) -> gl::types::GLint {
+        self.find_uniform(name, true)
+    }
+
+    fn find_uniform(&self, name: &str, warn_missing: bool) -> gl::types::GLint {
+        if let Some(&location) = self.uniform_locations.borrow().get(name) {
+            return location;
+        }
+
+        let c_name = CString::new(name).unwrap();
+        let location = unsafe { self.gl.GetUniformLocation(self.id, c_name.as_ptr()) };
+        if cfg!(debug_assertions) && warn_missing && location == -1 {
+            eprintln!(
+                "uniform `{name}` not found in shader program {}, it is misspelled or unused",
+                self.id
             );
         }
+
+        self.uniform_locations
+            .borrow_mut()
+            .insert(name.to_owned(), location);
+        location
     }
 
-    pub fn set_uniform_i32(&self, name: &str, value: i32) {
-        let name = CString::new(name).unwrap();
+    pub fn set_uniform_bool(&self, name: &str, value: bool) {
+        unsafe {
+            self.gl
+                .ProgramUniform1i(self.id, self.uniform_location(name), value.into());
+        }
+    }
 
+    pub fn set_uniform_i32(&self, name: &str, value: i32) {
         unsafe {
-            self.gl.ProgramUniform1i(
-                self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
-                value,
-            );
+            self.gl
+                .ProgramUniform1i(self.id, self.uniform_location(name), value);
         }
     }
 
     pub fn set_uniform_u32(&self, name: &str, value: u32) {
-        let name = CString::new(name).unwrap();
-
         unsafe {
-            self.gl.ProgramUniform1ui(
-                self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
-                value,
-            );
+            self.gl
+                .ProgramUniform1ui(self.id, self.uniform_location(name), value);
         }
     }
 
     pub fn set_uniform_f32(&self, name: &str, value: f32) {
-        let name = CString::new(name).unwrap();
-
         unsafe {
-            self.gl.ProgramUniform1f(
-                self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
-                value,
-            );
+            self.gl
+                .ProgramUniform1f(self.id, self.uniform_location(name), value);
         }
     }
 
+    #[allow(dead_code)]
     pub fn set_uniform_vec2(&self, name: &str, value: [f32; 2]) {
-        let name = CString::new(name).unwrap();
-
         unsafe {
-            self.gl.ProgramUniform2fv(
-                self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
-                1,
-                value.as_ptr(),
-            );
+            self.gl
+                .ProgramUniform2fv(self.id, self.uniform_location(name), 1, value.as_ptr());
         }
     }
 
     pub fn set_uniform_vec3(&self, name: &str, value: [f32; 3]) {
-        let name = CString::new(name).unwrap();
-
         unsafe {
-            self.gl.ProgramUniform3fv(
-                self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
-                1,
-                value.as_ptr(),
-            );
+            self.gl
+                .ProgramUniform3fv(self.id, self.uniform_location(name), 1, value.as_ptr());
         }
     }
 
+    #[allow(dead_code)]
     pub fn set_uniform_vec4(&self, name: &str, value: [f32; 4]) {
-        let name = CString::new(name).unwrap();
-
         unsafe {
-            self.gl.ProgramUniform4fv(
-                self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
-                1,
-                value.as_ptr(),
-            );
+            self.gl
+                .ProgramUniform4fv(self.id, self.uniform_location(name), 1, value.as_ptr());
         }
     }
 
+    #[allow(dead_code)]
     pub fn set_uniform_mat2(&self, name: &str, value: [f32; 4]) {
-        let name = CString::new(name).unwrap();
-
         unsafe {
             self.gl.ProgramUniformMatrix2fv(
                 self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
+                self.uniform_location(name),
                 1,
                 gl::FALSE,
                 value.as_ptr(),
@@ -325,12 +378,10 @@ impl ShaderProgram {
     }
 
     pub fn set_uniform_mat3(&self, name: &str, value: [f32; 9]) {
-        let name = CString::new(name).unwrap();
-
         unsafe {
             self.gl.ProgramUniformMatrix3fv(
                 self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
+                self.uniform_location(name),
                 1,
                 gl::FALSE,
                 value.as_ptr(),
@@ -339,12 +390,10 @@ impl ShaderProgram {
     }
 
     pub fn set_uniform_mat4(&self, name: &str, value: [f32; 16]) {
-        let name = CString::new(name).unwrap();
-
         unsafe {
             self.gl.ProgramUniformMatrix4fv(
                 self.id,
-                self.gl.GetUniformLocation(self.id, name.as_ptr()),
+                self.uniform_location(name),
                 1,
                 gl::FALSE,
                 &value[0],
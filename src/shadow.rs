@@ -0,0 +1,245 @@
+use glam::{Mat4, Vec3};
+
+use crate::{
+    array::{AttribPointer, Size, VerticesArray},
+    framebuffer::DepthFramebuffer,
+    projection,
+    shader_program::{ShaderProgram, ShaderProgramBuilder},
+    texture::{self, DepthCubemap, DepthTexture},
+};
+
+/// Shadow maps of the first directional light and the first point light.
+///
+/// The figure is rendered from each light into depth-only maps, which the polygon program
+/// samples through `uDirShadowMap` and `uPointShadowMap`.
+pub(crate) struct Shadows {
+    gl: gl::Gl,
+
+    framebuffer: DepthFramebuffer,
+    dir_map: DepthTexture,
+    point_map: DepthCubemap,
+
+    program: ShaderProgram,
+    array: VerticesArray,
+    slices_count: u32,
+
+    dir_light_space_matrix: Mat4,
+
+    enabled: bool,
+    bias: f32,
+}
+
+impl Shadows {
+    const DIR_MAP_SIZE: u32 = 2048;
+    const POINT_MAP_SIZE: u32 = 1024;
+    /// The shadow pass uses a coarser surface than the visible model, shadows don't need it.
+    const SLICES_COUNT: u32 = 200;
+
+    /// Distance from the origin the directional light renders its shadow map from.
+    const DIR_LIGHT_DISTANCE: f32 = 5.0;
+    const POINT_NEAR_PLANE: f32 = 0.05;
+    const POINT_FAR_PLANE: f32 = 25.0;
+
+    const BIAS_DEFAULT: f32 = 0.005;
+    const BIAS_MIN: f32 = 1e-5;
+    const BIAS_MAX: f32 = 0.1;
+    const BIAS_FACTOR: f32 = 1.5;
+
+    pub fn new(gl: gl::Gl) -> Self {
+        let program = ShaderProgramBuilder::new(gl.clone())
+            .include(
+                "surface.glsl",
+                include_bytes!("polygon_program/surface.glsl"),
+            )
+            .vertex_shader(include_bytes!("polygon_program/vertex_shader.glsl"))
+            .geometry_shader(include_bytes!("shadow_program/geometry_shader.glsl"))
+            .fragment_shader(include_bytes!("shadow_program/fragment_shader.glsl"))
+            .build()
+            .unwrap();
+
+        let mut angles = Vec::new();
+        for polar_index in 0..Self::SLICES_COUNT {
+            for azimuth_index in 0..Self::SLICES_COUNT {
+                angles.push(polar_index);
+                angles.push(azimuth_index);
+            }
+        }
+
+        let array = VerticesArray::new(gl.clone(), angles);
+
+        array.set_attrib_int_pointer(
+            program.attrib_location_of("iPolarAngleIndex"),
+            AttribPointer {
+                size: Size::One,
+                stride: 2 * std::mem::size_of::<u32>(),
+                offset: 0,
+                ty: gl::UNSIGNED_INT,
+            },
+        );
+        array.set_attrib_int_pointer(
+            program.attrib_location_of("iAzimuthAngleIndex"),
+            AttribPointer {
+                size: Size::One,
+                stride: 2 * std::mem::size_of::<u32>(),
+                offset: std::mem::size_of::<u32>(),
+                ty: gl::UNSIGNED_INT,
+            },
+        );
+
+        Self {
+            framebuffer: DepthFramebuffer::new(gl.clone()),
+            dir_map: DepthTexture::new(gl.clone(), Self::DIR_MAP_SIZE),
+            point_map: DepthCubemap::new(gl.clone(), Self::POINT_MAP_SIZE),
+            gl,
+
+            program,
+            array,
+            slices_count: Self::SLICES_COUNT,
+
+            dir_light_space_matrix: Mat4::IDENTITY,
+
+            enabled: true,
+            bias: Self::BIAS_DEFAULT,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Multiplies the depth bias by a constant factor, or divides it when `increase` is false.
+    pub fn scale_bias(&mut self, increase: bool) -> f32 {
+        let factor = if increase {
+            Self::BIAS_FACTOR
+        } else {
+            Self::BIAS_FACTOR.recip()
+        };
+
+        self.bias = (self.bias * factor).clamp(Self::BIAS_MIN, Self::BIAS_MAX);
+        self.bias
+    }
+
+    /// Renders the shadow maps and leaves the default framebuffer bound. The caller has to
+    /// restore the viewport afterwards.
+    pub fn render(
+        &mut self,
+        model_matrix: Mat4,
+        radius: f32,
+        dir_light_direction: Option<Vec3>,
+        point_light_position: Option<Vec3>,
+    ) {
+        self.array.use_array();
+        self.program.use_program();
+        self.program
+            .set_uniform_mat4("uModelMat", model_matrix.to_cols_array());
+        self.program.set_uniform_f32("uRadius", radius);
+        self.program
+            .set_uniform_u32("uSlicesCount", self.slices_count);
+
+        if let Some(direction) = dir_light_direction {
+            self.dir_light_space_matrix = Self::dir_light_space_matrix(direction, radius);
+
+            self.framebuffer.bind_texture(&self.dir_map);
+            self.program.set_uniform_bool("uLinearDepth", false);
+            self.program.set_uniform_mat4(
+                "uLightSpaceMat",
+                self.dir_light_space_matrix.to_cols_array(),
+            );
+            self.draw_depth();
+        }
+
+        if let Some(position) = point_light_position {
+            self.program.set_uniform_bool("uLinearDepth", true);
+            self.program
+                .set_uniform_vec3("uLightPos", position.to_array());
+            self.program
+                .set_uniform_f32("uFarPlane", Self::POINT_FAR_PLANE);
+
+            for (face, matrix) in Self::point_light_space_matrices(position)
+                .into_iter()
+                .enumerate()
+            {
+                self.framebuffer
+                    .bind_cubemap_face(&self.point_map, face as u32);
+                self.program
+                    .set_uniform_mat4("uLightSpaceMat", matrix.to_cols_array());
+                self.draw_depth();
+            }
+        }
+
+        self.framebuffer.unbind();
+    }
+
+    /// Binds the shadow maps and sets the shadow uniforms of a program including `lighting.glsl`.
+    pub fn upload(&self, program: &ShaderProgram) {
+        // Bound even when disabled, samplers of different types must never point to the same
+        // unit, even when unused.
+        texture::bind_sampler(program, "uDirShadowMap", &self.dir_map);
+        texture::bind_sampler(program, "uPointShadowMap", &self.point_map);
+
+        program.set_uniform_bool("uShadowsEnabled", self.enabled);
+        program.set_uniform_f32("uShadowBias", self.bias);
+        program.set_uniform_mat4(
+            "uDirLightSpaceMat",
+            self.dir_light_space_matrix.to_cols_array(),
+        );
+        program.set_uniform_f32("uPointShadowFarPlane", Self::POINT_FAR_PLANE);
+    }
+
+    fn draw_depth(&self) {
+        unsafe {
+            self.gl.Clear(gl::DEPTH_BUFFER_BIT);
+            self.gl.DrawArrays(gl::POINTS, 0, self.array.len() as i32);
+        }
+    }
+
+    /// Orthographic view of the figure along `direction`, fitting a sphere of `2 * radius`.
+    fn dir_light_space_matrix(direction: Vec3, radius: f32) -> Mat4 {
+        let direction = direction.normalize();
+        let eye = -direction * Self::DIR_LIGHT_DISTANCE;
+        let up = if direction.cross(Vec3::Y).length_squared() < 1e-6 {
+            Vec3::Z
+        } else {
+            Vec3::Y
+        };
+
+        let extent = 2.0 * radius;
+        let projection = projection::orthographic_lh_gl(
+            -extent,
+            extent,
+            -extent,
+            extent,
+            Self::DIR_LIGHT_DISTANCE - extent,
+            Self::DIR_LIGHT_DISTANCE + extent,
+        );
+
+        projection * Mat4::look_at_lh(eye, Vec3::ZERO, up)
+    }
+
+    /// One 90° view per cube face in the `TEXTURE_CUBE_MAP_POSITIVE_X` order, using the
+    /// right-handed conventions cube map lookups are defined in.
+    fn point_light_space_matrices(position: Vec3) -> [Mat4; 6] {
+        let projection = Mat4::perspective_rh_gl(
+            std::f32::consts::FRAC_PI_2,
+            1.0,
+            Self::POINT_NEAR_PLANE,
+            Self::POINT_FAR_PLANE,
+        );
+        let face = |direction: Vec3, up: Vec3| {
+            projection * Mat4::look_at_rh(position, position + direction, up)
+        };
+
+        [
+            face(Vec3::X, Vec3::NEG_Y),
+            face(Vec3::NEG_X, Vec3::NEG_Y),
+            face(Vec3::Y, Vec3::Z),
+            face(Vec3::NEG_Y, Vec3::NEG_Z),
+            face(Vec3::Z, Vec3::NEG_Y),
+            face(Vec3::NEG_Z, Vec3::NEG_Y),
+        ]
+    }
+}
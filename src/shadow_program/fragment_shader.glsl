@@ -0,0 +1,17 @@
+#version 330 core
+
+in vec3 FragPos;
+
+// Point light shadows store the distance to the light divided by the far plane instead of the
+// projected depth, so the lookup doesn't depend on the cube face.
+uniform bool uLinearDepth;
+uniform vec3 uLightPos;
+uniform float uFarPlane;
+
+void main() {
+  if (uLinearDepth) {
+    gl_FragDepth = length(FragPos - uLightPos) / uFarPlane;
+  } else {
+    gl_FragDepth = gl_FragCoord.z;
+  }
+}
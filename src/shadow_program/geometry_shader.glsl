@@ -0,0 +1,34 @@
+#version 330 core
+
+#include "surface.glsl"
+
+in uint PolarAngleIndex[1];
+in uint AzimuthAngleIndex[1];
+
+layout (points) in;
+layout (triangle_strip, max_vertices = 4) out;
+
+out vec3 FragPos;
+
+uniform uint uSlicesCount;
+uniform mat4 uModelMat;
+uniform mat4 uLightSpaceMat;
+
+void emitVertex(vec3 pos) {
+  vec4 worldPos = uModelMat * vec4(pos, 1.0);
+
+  FragPos = vec3(worldPos);
+  gl_Position = uLightSpaceMat * worldPos;
+  EmitVertex();
+}
+
+void main() {
+  uint nextPolarAngleIndex = PolarAngleIndex[0] + uint(1);
+  uint nextAzimuthAngleIndex = (AzimuthAngleIndex[0] + uint(1)) % uSlicesCount;
+
+  emitVertex(anglesToPos(nextPolarAngleIndex, AzimuthAngleIndex[0], uSlicesCount));
+  emitVertex(anglesToPos(PolarAngleIndex[0], AzimuthAngleIndex[0], uSlicesCount));
+  emitVertex(anglesToPos(nextPolarAngleIndex, nextAzimuthAngleIndex, uSlicesCount));
+  emitVertex(anglesToPos(PolarAngleIndex[0], nextAzimuthAngleIndex, uSlicesCount));
+  EndPrimitive();
+}
@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+
+use glam::Mat4;
+use serde::Deserialize;
+
+use crate::{
+    array::{AttribPointer, Size, VerticesArray},
+    error::TextureError,
+    shader_program::{ShaderProgram, ShaderProgramBuilder},
+    texture::{self, Cubemap},
+};
+
+/// Cube map drawn behind the figure, either one equirectangular panorama or six faces in the
+/// order +X, -X, +Y, -Y, +Z, -Z. Without either the background stays black.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct SkyboxConfig {
+    pub equirectangular: Option<PathBuf>,
+    pub faces: Option<[PathBuf; 6]>,
+}
+
+impl SkyboxConfig {
+    fn load(&self, gl: &gl::Gl) -> Option<Result<Cubemap, TextureError>> {
+        match (&self.equirectangular, &self.faces) {
+            (Some(path), _) => Some(Cubemap::open_equirectangular(gl.clone(), path)),
+            (None, Some(faces)) => Some(Cubemap::open_faces(gl.clone(), faces)),
+            (None, None) => None,
+        }
+    }
+}
+
+pub(crate) struct Skybox {
+    gl: gl::Gl,
+    program: ShaderProgram,
+    /// A single triangle covering the screen.
+    array: VerticesArray,
+    cubemap: Option<Cubemap>,
+    /// Bound as the environment map without a skybox, cube samplers must never be left on a
+    /// unit shared with 2D samplers.
+    fallback: Cubemap,
+    config: SkyboxConfig,
+}
+
+impl Skybox {
+    pub fn new(gl: gl::Gl) -> Self {
+        let program = ShaderProgramBuilder::new(gl.clone())
+            .include("output.glsl", include_bytes!("polygon_program/output.glsl"))
+            .vertex_shader(include_bytes!("skybox_program/vertex_shader.glsl"))
+            .fragment_shader(include_bytes!("skybox_program/fragment_shader.glsl"))
+            .build()
+            .unwrap();
+
+        let array = VerticesArray::new(gl.clone(), vec![-1.0f32, -1.0, 3.0, -1.0, -1.0, 3.0]);
+        array.set_attrib_pointer(
+            program.attrib_location_of("iPosition"),
+            AttribPointer {
+                size: Size::Two,
+                stride: 2 * std::mem::size_of::<f32>(),
+                offset: 0,
+                ty: gl::FLOAT,
+            },
+            false,
+        );
+
+        let black = image::DynamicImage::new_rgba8(1, 1);
+        let fallback = Cubemap::from_faces(gl.clone(), &vec![black; 6]).unwrap();
+
+        Self {
+            gl,
+            program,
+            array,
+            cubemap: None,
+            fallback,
+            config: SkyboxConfig::default(),
+        }
+    }
+
+    /// Loads the cube map of `config` unless it is already shown, a failure is reported and
+    /// leaves the background black.
+    pub fn configure(&mut self, config: &SkyboxConfig) {
+        if *config == self.config {
+            return;
+        }
+        self.config = config.clone();
+
+        self.cubemap = config.load(&self.gl).and_then(|cubemap| {
+            cubemap
+                .map_err(|err| eprintln!("{err}, the skybox is disabled"))
+                .ok()
+        });
+    }
+
+    /// Binds the skybox as `uEnvironmentMap` of a program including `lighting.glsl`, returns
+    /// whether there is one.
+    pub fn bind_environment(&self, program: &ShaderProgram) -> bool {
+        let cubemap = self.cubemap.as_ref().unwrap_or(&self.fallback);
+        texture::bind_sampler(program, "uEnvironmentMap", cubemap);
+
+        self.cubemap.is_some()
+    }
+
+    /// Fills the background, `matrix` comes from [`Camera::skybox_matrix`].
+    ///
+    /// [`Camera::skybox_matrix`]: crate::camera::Camera::skybox_matrix
+    pub fn draw(&self, matrix: Mat4, gamma_correction: bool) {
+        let Some(cubemap) = &self.cubemap else {
+            return;
+        };
+
+        self.array.use_array();
+        self.program.use_program();
+        texture::bind_sampler(&self.program, "uSkybox", cubemap);
+        self.program
+            .set_uniform_mat4("uInverseViewProjectionMat", matrix.to_cols_array());
+        self.program
+            .set_uniform_bool("uGammaCorrection", gamma_correction);
+
+        unsafe {
+            // Everything drawn afterwards covers the skybox. The triangle lies on the far plane,
+            // where the default LESS test fails against the cleared depth of 1.
+            self.gl.DepthMask(gl::FALSE);
+            self.gl.DepthFunc(gl::LEQUAL);
+            self.gl.DrawArrays(gl::TRIANGLES, 0, 3);
+            self.gl.DepthFunc(gl::LESS);
+            self.gl.DepthMask(gl::TRUE);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#version 330 core
+
+#include "output.glsl"
+
+in vec3 Direction;
+
+out vec4 FragColor;
+
+uniform samplerCube uSkybox;
+
+void main() {
+  FragColor = vec4(encodeOutput(texture(uSkybox, Direction).rgb), 1.0);
+}
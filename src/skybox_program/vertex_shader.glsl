@@ -0,0 +1,16 @@
+#version 330 core
+
+in vec2 iPosition;
+
+out vec3 Direction;
+
+// Maps clip space positions to world space view directions.
+uniform mat4 uInverseViewProjectionMat;
+
+void main() {
+  vec4 direction = uInverseViewProjectionMat * vec4(iPosition, 1.0, 1.0);
+  Direction = direction.xyz / direction.w;
+
+  // On the far plane, behind everything else.
+  gl_Position = vec4(iPosition, 1.0, 1.0);
+}
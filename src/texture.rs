@@ -1,55 +1,961 @@
-use std::path::Path;
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use glam::Vec3;
+use serde::Deserialize;
+
+use crate::{
+    error::TextureError,
+    shader_program::ShaderProgram,
+    texture_data::{self, BlockFormat, CompressedData, PixelData, TextureData},
+    watcher::FileWatcher,
+};
+
+// Not part of the GLES 3.2 bindings.
+const TEXTURE_LOD_BIAS: gl::types::GLenum = 0x8501;
+const TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FF;
+
+/// How the channels of an image are interpreted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ColorSpace {
+    /// Colors meant to be looked at, decoded to linear by the sampler.
+    Srgb,
+    /// Data such as normals, roughness or specular intensity, sampled as stored.
+    Linear,
+}
+
+/// How texture coordinates outside of `0..1` are handled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// Samples outside of the texture return [`TextureOptions::border_color`].
+    ClampToBorder,
+}
+
+impl Wrap {
+    fn to_gl(self) -> gl::types::GLenum {
+        match self {
+            Self::Repeat => gl::REPEAT,
+            Self::MirroredRepeat => gl::MIRRORED_REPEAT,
+            Self::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Self::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// Filter used when a texel covers more than one pixel.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MagFilter {
+    Nearest,
+    Linear,
+}
+
+impl MagFilter {
+    fn to_gl(self) -> gl::types::GLenum {
+        match self {
+            Self::Nearest => gl::NEAREST,
+            Self::Linear => gl::LINEAR,
+        }
+    }
+}
+
+/// Filter used when a pixel covers more than one texel, the mipmap variants name the filter
+/// within a level first and between levels second.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MinFilter {
+    Nearest,
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    #[serde(alias = "trilinear")]
+    LinearMipmapLinear,
+}
+
+impl MinFilter {
+    fn to_gl(self) -> gl::types::GLenum {
+        match self {
+            Self::Nearest => gl::NEAREST,
+            Self::Linear => gl::LINEAR,
+            Self::NearestMipmapNearest => gl::NEAREST_MIPMAP_NEAREST,
+            Self::LinearMipmapNearest => gl::LINEAR_MIPMAP_NEAREST,
+            Self::NearestMipmapLinear => gl::NEAREST_MIPMAP_LINEAR,
+            Self::LinearMipmapLinear => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
+
+/// Sampling state of a [`Texture`], built with the setters below or read from the scene file.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct TextureOptions {
+    pub wrap_s: Wrap,
+    pub wrap_t: Wrap,
+    pub min_filter: MinFilter,
+    pub mag_filter: MagFilter,
+    /// Maximum anisotropy, 1 turns anisotropic filtering off. Clamped to what the driver
+    /// supports and ignored without the extension.
+    pub anisotropy: f32,
+    pub border_color: [f32; 4],
+    /// Added to the mipmap level the sampler picks, positive values blur.
+    pub lod_bias: f32,
+}
+
+impl TextureOptions {
+    pub fn wrap(self, wrap: Wrap) -> Self {
+        self.wrap_s(wrap).wrap_t(wrap)
+    }
+
+    pub fn wrap_s(self, wrap_s: Wrap) -> Self {
+        Self { wrap_s, ..self }
+    }
+
+    pub fn wrap_t(self, wrap_t: Wrap) -> Self {
+        Self { wrap_t, ..self }
+    }
+
+    pub fn min_filter(self, min_filter: MinFilter) -> Self {
+        Self { min_filter, ..self }
+    }
+
+    pub fn mag_filter(self, mag_filter: MagFilter) -> Self {
+        Self { mag_filter, ..self }
+    }
+
+    pub fn anisotropy(self, anisotropy: f32) -> Self {
+        Self { anisotropy, ..self }
+    }
+
+    pub fn border_color(self, border_color: [f32; 4]) -> Self {
+        Self {
+            border_color,
+            ..self
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn lod_bias(self, lod_bias: f32) -> Self {
+        Self { lod_bias, ..self }
+    }
+
+    /// Sets the parameters of the texture bound to `target`.
+    fn apply(&self, gl: &gl::Gl, target: gl::types::GLenum) {
+        unsafe {
+            gl.TexParameteri(target, gl::TEXTURE_WRAP_S, self.wrap_s.to_gl() as i32);
+            gl.TexParameteri(target, gl::TEXTURE_WRAP_T, self.wrap_t.to_gl() as i32);
+            gl.TexParameteri(
+                target,
+                gl::TEXTURE_MIN_FILTER,
+                self.min_filter.to_gl() as i32,
+            );
+            gl.TexParameteri(
+                target,
+                gl::TEXTURE_MAG_FILTER,
+                self.mag_filter.to_gl() as i32,
+            );
+            gl.TexParameterfv(target, gl::TEXTURE_BORDER_COLOR, self.border_color.as_ptr());
+            // Desktop only, skipped when unused so GLES contexts don't report an error.
+            if self.lod_bias != 0.0 {
+                gl.TexParameterf(target, TEXTURE_LOD_BIAS, self.lod_bias);
+            }
+
+            if let Some(max_anisotropy) = max_anisotropy(gl) {
+                let anisotropy = self.anisotropy.clamp(1.0, max_anisotropy);
+                gl.TexParameterf(target, TEXTURE_MAX_ANISOTROPY, anisotropy);
+            }
+        }
+    }
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            wrap_s: Wrap::Repeat,
+            wrap_t: Wrap::Repeat,
+            min_filter: MinFilter::LinearMipmapLinear,
+            mag_filter: MagFilter::Linear,
+            anisotropy: 8.0,
+            border_color: [0.0, 0.0, 0.0, 1.0],
+            lod_bias: 0.0,
+        }
+    }
+}
+
+/// Largest supported anisotropy, `None` without anisotropic filtering support. Queried once,
+/// the renderer only ever creates one context.
+fn max_anisotropy(gl: &gl::Gl) -> Option<f32> {
+    static MAX_ANISOTROPY: OnceLock<Option<f32>> = OnceLock::new();
+
+    *MAX_ANISOTROPY.get_or_init(|| {
+        let supported = has_extension(
+            gl,
+            &[
+                "GL_EXT_texture_filter_anisotropic",
+                "GL_ARB_texture_filter_anisotropic",
+            ],
+        );
+
+        supported.then(|| {
+            let mut max = 1.0;
+            unsafe { gl.GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max) };
+            max
+        })
+    })
+}
+
+/// Whether any of the `names` extensions is supported.
+fn has_extension(gl: &gl::Gl, names: &[&str]) -> bool {
+    unsafe {
+        let mut count = 0;
+        gl.GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+
+        (0..count as u32).any(|index| {
+            let name = gl.GetStringi(gl::EXTENSIONS, index);
+            !name.is_null()
+                && names.contains(
+                    &std::ffi::CStr::from_ptr(name as *const _)
+                        .to_str()
+                        .unwrap_or_default(),
+                )
+        })
+    }
+}
+
+/// Whether `block` can be uploaded without decompressing it first.
+fn supports_compressed(gl: &gl::Gl, block: BlockFormat, color_space: ColorSpace) -> bool {
+    let formats = unsafe {
+        let mut count = 0;
+        gl.GetIntegerv(gl::NUM_COMPRESSED_TEXTURE_FORMATS, &mut count);
+        let mut formats = vec![0; count.max(0) as usize];
+        gl.GetIntegerv(gl::COMPRESSED_TEXTURE_FORMATS, formats.as_mut_ptr());
+        formats
+    };
+
+    formats.contains(&(block.internal_format(color_space) as i32))
+        || has_extension(gl, block.extensions())
+}
 
 pub(crate) struct Texture {
     id: u32,
     gl: gl::Gl,
+    /// Set for textures opened from a file, which are reloaded when it changes.
+    source: Option<TextureSource>,
+}
+
+struct TextureSource {
+    watcher: FileWatcher,
+    color_space: ColorSpace,
+    options: TextureOptions,
 }
 
 impl Texture {
-    pub fn open<P: AsRef<Path>>(gl: gl::Gl, path: P) -> Self {
-        let img = image::io::Reader::open(path)
-            .unwrap()
-            .decode()
-            .unwrap()
-            .to_rgb16();
+    /// Loads the image at `path`, relative paths are resolved with [`resolve_path`]. The format
+    /// follows the file, see [`TextureData::decode`].
+    pub fn open<P: AsRef<Path>>(
+        gl: gl::Gl,
+        path: P,
+        color_space: ColorSpace,
+        options: &TextureOptions,
+    ) -> Result<Self, TextureError> {
+        let path = resolve_path(path);
+        // Created before decoding so changes made in the meantime aren't missed.
+        let watcher = FileWatcher::new(&path);
+
+        let data = load(&gl, &path, color_space)?;
+        let mut texture = Self::generate(gl);
+        texture.upload(&data, options);
+        texture.source = Some(TextureSource {
+            watcher,
+            color_space,
+            options: *options,
+        });
+
+        Ok(texture)
+    }
+
+    /// Uploads an already decoded or generated image.
+    pub fn from_image(
+        gl: gl::Gl,
+        img: &image::DynamicImage,
+        color_space: ColorSpace,
+        options: &TextureOptions,
+    ) -> Self {
+        let texture = Self::generate(gl);
+        let data = TextureData::Pixels(PixelData::from_image(img, color_space));
+        texture.upload(&data, options);
+
+        texture
+    }
+
+    /// Watches `path` as if the texture had been opened from it, so a placeholder shown in
+    /// place of a file that failed to load is replaced once the file loads. Relative paths are
+    /// resolved with [`resolve_path`].
+    pub fn watching<P: AsRef<Path>>(
+        mut self,
+        path: P,
+        color_space: ColorSpace,
+        options: &TextureOptions,
+    ) -> Self {
+        self.source = Some(TextureSource {
+            watcher: FileWatcher::new(resolve_path(path)),
+            color_space,
+            options: *options,
+        });
+
+        self
+    }
+
+    fn generate(gl: gl::Gl) -> Self {
+        let mut texture_id = 0;
+        unsafe { gl.GenTextures(1, &mut texture_id) };
+
+        Self {
+            gl,
+            id: texture_id,
+            source: None,
+        }
+    }
+
+    /// Re-uploads the image once its file has changed, `dt` is the time in seconds since the
+    /// last call. The current image is kept when the file can't be loaded.
+    pub fn poll(&mut self, dt: f32) -> Option<Result<&Path, TextureError>> {
+        let source = self.source.as_mut()?;
+        if !source.watcher.poll(dt) {
+            return None;
+        }
+
+        let source = self.source.as_ref()?;
+        Some(
+            load(&self.gl, source.watcher.path(), source.color_space).map(|data| {
+                self.upload(&data, &source.options);
+                source.watcher.path()
+            }),
+        )
+    }
+
+    fn upload(&self, data: &TextureData, options: &TextureOptions) {
+        let gl = &self.gl;
+
+        unsafe {
+            gl.BindTexture(gl::TEXTURE_2D, self.id);
+            // Rows of RGB images aren't necessarily 4-byte aligned.
+            gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+
+            let max_level = match data {
+                TextureData::Pixels(pixels) => {
+                    gl.TexImage2D(
+                        gl::TEXTURE_2D,
+                        0,
+                        pixels.internal_format as i32,
+                        pixels.width as i32,
+                        pixels.height as i32,
+                        0,
+                        pixels.format,
+                        pixels.ty,
+                        pixels.bytes.as_ptr() as *const _,
+                    );
+                    gl.GenerateMipmap(gl::TEXTURE_2D);
+                    // The default, a reload may replace a compressed image.
+                    1000
+                }
+                TextureData::Compressed(compressed) => {
+                    for (level, bytes) in (0..).zip(&compressed.levels) {
+                        let (width, height) = CompressedData::level_dimensions(
+                            compressed.width,
+                            compressed.height,
+                            level,
+                        );
+                        gl.CompressedTexImage2D(
+                            gl::TEXTURE_2D,
+                            level as i32,
+                            compressed.internal_format,
+                            width as i32,
+                            height as i32,
+                            0,
+                            bytes.len() as i32,
+                            bytes.as_ptr() as *const _,
+                        );
+                    }
+                    // Mipmaps can't be generated for compressed formats, only the levels of
+                    // the file are used.
+                    compressed.levels.len() as i32 - 1
+                }
+            };
+            gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, max_level);
+
+            let swizzles = [
+                gl::TEXTURE_SWIZZLE_R,
+                gl::TEXTURE_SWIZZLE_G,
+                gl::TEXTURE_SWIZZLE_B,
+                gl::TEXTURE_SWIZZLE_A,
+            ];
+            for (swizzle, source) in swizzles.into_iter().zip(data.swizzle()) {
+                gl.TexParameteri(gl::TEXTURE_2D, swizzle, source as i32);
+            }
+        }
+        options.apply(gl, gl::TEXTURE_2D);
+    }
+}
+
+/// Reads the file at `path`, keeping compressed payloads the driver supports.
+fn load(gl: &gl::Gl, path: &Path, color_space: ColorSpace) -> Result<TextureData, TextureError> {
+    TextureData::decode(&read(path)?, color_space, |block| {
+        supports_compressed(gl, block, color_space)
+    })
+    .map_err(|source| TextureError::Decode {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn decode(path: &Path) -> Result<image::DynamicImage, TextureError> {
+    texture_data::decode_image(&read(path)?).map_err(|source| TextureError::Decode {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, TextureError> {
+    std::fs::read(path).map_err(|source| TextureError::Open {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+impl BindToUnit for Texture {
+    const KIND: SamplerKind = SamplerKind::Texture2D;
+
+    fn bind_to_unit(&self, unit: u32) {
+        unsafe {
+            self.gl.ActiveTexture(gl::TEXTURE0 + unit);
+            self.gl.BindTexture(gl::TEXTURE_2D, self.id);
+        }
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// Color cube map sampled by direction, faces are in the GL order +X, -X, +Y, -Y, +Z, -Z.
+pub(crate) struct Cubemap {
+    id: u32,
+    gl: gl::Gl,
+}
+
+impl Cubemap {
+    /// Loads six face images, relative paths are resolved with [`resolve_path`].
+    pub fn open_faces<P: AsRef<Path>>(gl: gl::Gl, paths: &[P; 6]) -> Result<Self, TextureError> {
+        let mut faces = Vec::with_capacity(6);
+        for path in paths {
+            faces.push(decode(&resolve_path(path))?);
+        }
+
+        Self::from_faces(gl, &faces)
+    }
+
+    /// Loads a latitude/longitude panorama and projects it onto the faces.
+    pub fn open_equirectangular<P: AsRef<Path>>(gl: gl::Gl, path: P) -> Result<Self, TextureError> {
+        let img = decode(&resolve_path(path))?;
+        Self::from_faces(gl, &equirectangular_to_faces(&img))
+    }
+
+    const FACE_NAMES: [&'static str; 6] = ["+X", "-X", "+Y", "-Y", "+Z", "-Z"];
 
-        let width = img.width();
-        let height = img.height();
-        let bytes = img.into_vec();
-        // dbg!(width, height, bytes.len());
+    /// Uploads six images, see [`Cubemap`] for their order. Float images are kept in high
+    /// dynamic range, all faces must share the same format. The faces must be square and of
+    /// the same size, GL can't sample the cube map otherwise.
+    pub fn from_faces(gl: gl::Gl, faces: &[image::DynamicImage]) -> Result<Self, TextureError> {
+        let size = faces.first().map_or(0, |face| face.width());
+        for (face, name) in faces.iter().zip(Self::FACE_NAMES) {
+            if face.width() != size || face.height() != size {
+                return Err(TextureError::FaceSize {
+                    face: name,
+                    width: face.width(),
+                    height: face.height(),
+                    size,
+                });
+            }
+        }
 
         let mut texture_id = 0;
 
         unsafe {
             gl.GenTextures(1, &mut texture_id);
-            gl.BindTexture(gl::TEXTURE_2D, texture_id);
+            gl.BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
+            gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
 
-            gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-            gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            for (target, face) in (gl::TEXTURE_CUBE_MAP_POSITIVE_X..).zip(faces) {
+                let pixels = PixelData::from_image(face, ColorSpace::Srgb);
+                gl.TexImage2D(
+                    target,
+                    0,
+                    pixels.internal_format as i32,
+                    pixels.width as i32,
+                    pixels.height as i32,
+                    0,
+                    pixels.format,
+                    pixels.ty,
+                    pixels.bytes.as_ptr() as *const _,
+                );
+            }
+
+            gl.GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+            // The options only cover the two axes of 2D textures.
+            gl.TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_R,
+                gl::CLAMP_TO_EDGE as i32,
+            );
+        }
+        TextureOptions::default()
+            .wrap(Wrap::ClampToEdge)
+            .apply(&gl, gl::TEXTURE_CUBE_MAP);
+
+        Ok(Self { gl, id: texture_id })
+    }
+}
+
+impl BindToUnit for Cubemap {
+    const KIND: SamplerKind = SamplerKind::Cubemap;
+
+    fn bind_to_unit(&self, unit: u32) {
+        unsafe {
+            self.gl.ActiveTexture(gl::TEXTURE0 + unit);
+            self.gl.BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+        }
+    }
+}
+
+impl Drop for Cubemap {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// Resamples a latitude/longitude panorama into six cube faces a quarter of its width wide.
+/// The center of the panorama ends up on the +Z face, faces are floats only for float panoramas.
+fn equirectangular_to_faces(img: &image::DynamicImage) -> Vec<image::DynamicImage> {
+    use std::f32::consts::PI;
+
+    let hdr = matches!(
+        img,
+        image::DynamicImage::ImageRgb32F(_) | image::DynamicImage::ImageRgba32F(_)
+    );
+    let panorama = img.to_rgba32f();
+    let face_size = (panorama.width() / 4).max(1);
+
+    // Direction through the face point `(s, t)` in `-1..1`, following the GL cube map layout
+    // where `t` grows downwards on the side faces.
+    let directions: [fn(f32, f32) -> Vec3; 6] = [
+        |s, t| Vec3::new(1.0, -t, -s),
+        |s, t| Vec3::new(-1.0, -t, s),
+        |s, t| Vec3::new(s, 1.0, t),
+        |s, t| Vec3::new(s, -1.0, -t),
+        |s, t| Vec3::new(s, -t, 1.0),
+        |s, t| Vec3::new(-s, -t, -1.0),
+    ];
+
+    directions
+        .iter()
+        .map(|direction| {
+            let face = image::Rgba32FImage::from_fn(face_size, face_size, |x, y| {
+                let s = (x as f32 + 0.5) / face_size as f32 * 2.0 - 1.0;
+                let t = (y as f32 + 0.5) / face_size as f32 * 2.0 - 1.0;
+                let direction = direction(s, t).normalize();
+
+                let u = 0.5 + direction.x.atan2(direction.z) / (2.0 * PI);
+                let v = 0.5 - direction.y.asin() / PI;
+                sample_bilinear(&panorama, u, v)
+            });
+
+            let face = image::DynamicImage::ImageRgba32F(face);
+            if hdr {
+                face
+            } else {
+                image::DynamicImage::ImageRgba8(face.to_rgba8())
+            }
+        })
+        .collect()
+}
+
+/// Samples `img` at `(u, v)` in `0..1`, wrapping horizontally and clamping vertically.
+fn sample_bilinear(img: &image::Rgba32FImage, u: f32, v: f32) -> image::Rgba<f32> {
+    let (width, height) = img.dimensions();
+    let x = u * width as f32 - 0.5;
+    let y = (v * height as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let (fx, fy) = (x - x0, y - y0);
+
+    let texel = |x: f32, y: f32| {
+        let x = (x as i64).rem_euclid(width as i64) as u32;
+        let y = (y as u32).min(height - 1);
+        img.get_pixel(x, y).0
+    };
+    let [a, b, c, d] = [
+        texel(x0, y0),
+        texel(x0 + 1.0, y0),
+        texel(x0, y0 + 1.0),
+        texel(x0 + 1.0, y0 + 1.0),
+    ];
+
+    image::Rgba(std::array::from_fn(|i| {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        top + (bottom - top) * fy
+    }))
+}
+
+/// Type of a sampler uniform, samplers of different types must never point to the same unit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum SamplerKind {
+    Texture2D,
+    Cubemap,
+}
+
+/// Texture that can be bound to a texture unit.
+pub(crate) trait BindToUnit {
+    /// Type of the samplers the texture is sampled through.
+    const KIND: SamplerKind;
+
+    fn bind_to_unit(&self, unit: u32);
+}
+
+/// Texture unit of every sampler in the shaders. A sampler nothing is bound to in a draw still
+/// points to its unit, so each sampler gets its own instead of the next free one. Unit 0 is left
+/// to texture uploads.
+const SAMPLER_UNITS: [(&str, u32, SamplerKind); 12] = [
+    ("uMaterial.diffuseMap", 1, SamplerKind::Texture2D),
+    ("uMaterial.specularMap", 2, SamplerKind::Texture2D),
+    ("uMaterial.emissiveMap", 3, SamplerKind::Texture2D),
+    ("uNormalMap", 4, SamplerKind::Texture2D),
+    ("uEnvironmentMap", 5, SamplerKind::Cubemap),
+    ("uDirShadowMap", 6, SamplerKind::Texture2D),
+    ("uPointShadowMap", 7, SamplerKind::Cubemap),
+    ("uPbrMaterial.albedoMap", 8, SamplerKind::Texture2D),
+    ("uPbrMaterial.metallicMap", 9, SamplerKind::Texture2D),
+    ("uPbrMaterial.roughnessMap", 10, SamplerKind::Texture2D),
+    ("uPbrMaterial.aoMap", 11, SamplerKind::Texture2D),
+    ("uSkybox", 12, SamplerKind::Cubemap),
+];
+
+/// Points every sampler of `program` to its unit, including the ones nothing is bound to in
+/// this draw. Called before each draw since programs can be rebuilt.
+pub(crate) fn assign_sampler_units(program: &ShaderProgram) {
+    for (sampler, unit, _) in SAMPLER_UNITS {
+        if program.has_uniform(sampler) {
+            program.set_uniform_i32(sampler, unit as i32);
+        }
+    }
+}
+
+/// Binds `texture` to the unit of `sampler` and sets the `sampler` uniform of `program` to it.
+pub(crate) fn bind_sampler<T: BindToUnit>(program: &ShaderProgram, sampler: &str, texture: &T) {
+    let Some(&(_, unit, kind)) = SAMPLER_UNITS.iter().find(|(name, ..)| *name == sampler) else {
+        panic!("sampler `{sampler}` has no texture unit");
+    };
+    debug_assert_eq!(
+        kind,
+        T::KIND,
+        "`{sampler}` is bound to the wrong texture type"
+    );
+
+    texture.bind_to_unit(unit);
+    program.set_uniform_i32(sampler, unit as i32);
+}
+
+/// Resolves a relative `path` against the current directory, or against the directory of the
+/// executable when it doesn't exist there. Paths found in neither are returned unchanged.
+pub(crate) fn resolve_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    if path.is_absolute() || path.exists() {
+        return path.to_path_buf();
+    }
+
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(path)))
+        .filter(|exe_relative| exe_relative.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Depth-only 2D texture used as a shadow map render target.
+pub(crate) struct DepthTexture {
+    id: u32,
+    size: u32,
+    gl: gl::Gl,
+}
+
+impl DepthTexture {
+    pub fn new(gl: gl::Gl, size: u32) -> Self {
+        let mut texture_id = 0;
+
+        unsafe {
+            gl.GenTextures(1, &mut texture_id);
+            gl.BindTexture(gl::TEXTURE_2D, texture_id);
 
             gl.TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGB as i32,
-                width as i32,
-                height as i32,
+                gl::DEPTH_COMPONENT24 as i32,
+                size as i32,
+                size as i32,
                 0,
-                gl::RGB,
-                gl::UNSIGNED_SHORT,
-                bytes.as_ptr() as *const _,
+                gl::DEPTH_COMPONENT,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
             );
-            gl.GenerateMipmap(gl::TEXTURE_2D);
         }
+        // Everything outside of the shadow map is lit.
+        TextureOptions::default()
+            .wrap(Wrap::ClampToBorder)
+            .border_color([1.0; 4])
+            .min_filter(MinFilter::Nearest)
+            .mag_filter(MagFilter::Nearest)
+            .anisotropy(1.0)
+            .apply(&gl, gl::TEXTURE_2D);
+
+        Self {
+            gl,
+            id: texture_id,
+            size,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
 
-        Self { gl, id: texture_id }
+    pub fn size(&self) -> u32 {
+        self.size
     }
+}
 
-    pub fn bind(&self) {
+impl BindToUnit for DepthTexture {
+    const KIND: SamplerKind = SamplerKind::Texture2D;
+
+    fn bind_to_unit(&self, unit: u32) {
         unsafe {
-            self.gl.ActiveTexture(gl::TEXTURE0);
+            self.gl.ActiveTexture(gl::TEXTURE0 + unit);
             self.gl.BindTexture(gl::TEXTURE_2D, self.id);
         }
     }
 }
+
+impl Drop for DepthTexture {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// Depth-only cube map used as a point light shadow map render target.
+pub(crate) struct DepthCubemap {
+    id: u32,
+    size: u32,
+    gl: gl::Gl,
+}
+
+impl DepthCubemap {
+    pub fn new(gl: gl::Gl, size: u32) -> Self {
+        let mut texture_id = 0;
+
+        unsafe {
+            gl.GenTextures(1, &mut texture_id);
+            gl.BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
+
+            for face in 0..6 {
+                gl.TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                    0,
+                    gl::DEPTH_COMPONENT24 as i32,
+                    size as i32,
+                    size as i32,
+                    0,
+                    gl::DEPTH_COMPONENT,
+                    gl::UNSIGNED_INT,
+                    std::ptr::null(),
+                );
+            }
+
+            gl.TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_S,
+                gl::CLAMP_TO_EDGE as i32,
+            );
+            gl.TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_T,
+                gl::CLAMP_TO_EDGE as i32,
+            );
+            gl.TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_R,
+                gl::CLAMP_TO_EDGE as i32,
+            );
+            gl.TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MIN_FILTER,
+                gl::NEAREST as i32,
+            );
+            gl.TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MAG_FILTER,
+                gl::NEAREST as i32,
+            );
+        }
+
+        Self {
+            gl,
+            id: texture_id,
+            size,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+
+impl BindToUnit for DepthCubemap {
+    const KIND: SamplerKind = SamplerKind::Cubemap;
+
+    fn bind_to_unit(&self, unit: u32) {
+        unsafe {
+            self.gl.ActiveTexture(gl::TEXTURE0 + unit);
+            self.gl.BindTexture(gl::TEXTURE_CUBE_MAP, self.id);
+        }
+    }
+}
+
+impl Drop for DepthCubemap {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{resolve_path, MagFilter, MinFilter, TextureOptions, Wrap, SAMPLER_UNITS};
+
+    #[test]
+    fn options_builder_sets_only_the_given_fields() {
+        let options = TextureOptions::default()
+            .wrap(Wrap::MirroredRepeat)
+            .wrap_t(Wrap::ClampToBorder)
+            .min_filter(MinFilter::Nearest)
+            .mag_filter(MagFilter::Nearest)
+            .anisotropy(1.0)
+            .border_color([1.0; 4])
+            .lod_bias(-0.5);
+
+        assert_eq!(
+            options,
+            TextureOptions {
+                wrap_s: Wrap::MirroredRepeat,
+                wrap_t: Wrap::ClampToBorder,
+                min_filter: MinFilter::Nearest,
+                mag_filter: MagFilter::Nearest,
+                anisotropy: 1.0,
+                border_color: [1.0; 4],
+                lod_bias: -0.5,
+            }
+        );
+    }
+
+    #[test]
+    fn options_deserialize_with_defaults() {
+        let options: TextureOptions = toml::from_str(
+            r#"
+            wrap_s = "clamp_to_edge"
+            min_filter = "trilinear"
+            lod_bias = 0.5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            options,
+            TextureOptions {
+                wrap_s: Wrap::ClampToEdge,
+                min_filter: MinFilter::LinearMipmapLinear,
+                lod_bias: 0.5,
+                ..TextureOptions::default()
+            }
+        );
+    }
+
+    #[test]
+    fn options_reject_unknown_filters_and_fields() {
+        assert!(toml::from_str::<TextureOptions>(r#"min_filter = "bilinear""#).is_err());
+        assert!(toml::from_str::<TextureOptions>("anisotropic = 4.0").is_err());
+    }
+
+    #[test]
+    fn samplers_never_share_a_unit() {
+        // A shared unit would only be fine for samplers of the same type that are never used
+        // together, giving every sampler its own keeps stale samplers from clashing.
+        for (index, (sampler, unit, _)) in SAMPLER_UNITS.iter().enumerate() {
+            assert_ne!(*unit, 0, "`{sampler}` uses the unit left to uploads");
+            for (other, other_unit, _) in &SAMPLER_UNITS[index + 1..] {
+                assert_ne!(unit, other_unit, "`{sampler}` and `{other}` share a unit");
+            }
+        }
+    }
+
+    #[test]
+    fn resolve_path_prefers_current_dir() {
+        // Tests run from the package root.
+        assert_eq!(resolve_path("Cargo.toml"), Path::new("Cargo.toml"));
+    }
+
+    #[test]
+    fn resolve_path_falls_back_to_exe_dir() {
+        let name = format!("resolve_path_{}.txt", std::process::id());
+        let exe_relative = std::env::current_exe()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join(&name);
+        assert!(!Path::new(&name).exists());
+
+        std::fs::write(&exe_relative, "").unwrap();
+        let resolved = resolve_path(&name);
+        std::fs::remove_file(&exe_relative).unwrap();
+
+        assert_eq!(resolved, exe_relative);
+    }
+
+    #[test]
+    fn resolve_path_keeps_missing_and_absolute_paths() {
+        assert_eq!(resolve_path("missing.png"), Path::new("missing.png"));
+
+        let absolute = std::env::temp_dir().join("missing.png");
+        assert_eq!(resolve_path(&absolute), absolute);
+    }
+}
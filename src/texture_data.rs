@@ -0,0 +1,793 @@
+use image::{
+    codecs::hdr::HdrDecoder,
+    error::{DecodingError, ImageFormatHint, UnsupportedError, UnsupportedErrorKind},
+    DynamicImage, ImageError, ImageFormat, Rgb32FImage,
+};
+
+use crate::texture::ColorSpace;
+
+// Desktop formats missing from the GLES 3.2 bindings.
+const R16: gl::types::GLenum = 0x822A;
+const RG16: gl::types::GLenum = 0x822C;
+const RGB16: gl::types::GLenum = 0x8054;
+const RGBA16: gl::types::GLenum = 0x805B;
+const COMPRESSED_RGBA_S3TC_DXT1: gl::types::GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: gl::types::GLenum = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: gl::types::GLenum = 0x83F3;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: gl::types::GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: gl::types::GLenum = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: gl::types::GLenum = 0x8C4F;
+const COMPRESSED_RED_RGTC1: gl::types::GLenum = 0x8DBB;
+const COMPRESSED_RG_RGTC2: gl::types::GLenum = 0x8DBD;
+const COMPRESSED_RGBA_BPTC_UNORM: gl::types::GLenum = 0x8E8C;
+const COMPRESSED_SRGB_ALPHA_BPTC_UNORM: gl::types::GLenum = 0x8E8D;
+
+/// Channels sampled as gray, the alpha channel (if any) comes from `alpha`.
+const fn gray_swizzle(alpha: gl::types::GLenum) -> [gl::types::GLenum; 4] {
+    [gl::RED, gl::RED, gl::RED, alpha]
+}
+const IDENTITY_SWIZZLE: [gl::types::GLenum; 4] = [gl::RED, gl::GREEN, gl::BLUE, gl::ALPHA];
+
+/// An image file turned into what `glTexImage2D` or `glCompressedTexImage2D` takes.
+pub(crate) enum TextureData {
+    Pixels(PixelData),
+    Compressed(CompressedData),
+}
+
+impl TextureData {
+    /// Picks the GL format from the image file in `bytes`. DDS and KTX2 payloads are kept
+    /// compressed when `supported` accepts their block format, otherwise DDS files are
+    /// decompressed on the CPU and KTX2 files fail.
+    pub fn decode(
+        bytes: &[u8],
+        color_space: ColorSpace,
+        supported: impl Fn(BlockFormat) -> bool,
+    ) -> Result<Self, ImageError> {
+        if let Some(compressed) = CompressedData::parse_ktx2(bytes, color_space) {
+            let compressed = compressed?;
+            if !supported(compressed.block) {
+                return Err(unsupported(
+                    "KTX2",
+                    format!(
+                        "{:?} blocks aren't supported by the driver",
+                        compressed.block
+                    ),
+                ));
+            }
+            return Ok(Self::Compressed(compressed));
+        }
+
+        if let Some(compressed) = CompressedData::parse_dds(bytes, color_space) {
+            let compressed = compressed?;
+            if supported(compressed.block) {
+                return Ok(Self::Compressed(compressed));
+            }
+        }
+
+        let img = decode_image(bytes)?;
+        Ok(Self::Pixels(PixelData::from_image(&img, color_space)))
+    }
+
+    /// Sources of the red, green, blue and alpha channels as seen by the shaders.
+    pub fn swizzle(&self) -> [gl::types::GLenum; 4] {
+        match self {
+            Self::Pixels(pixels) => pixels.swizzle,
+            Self::Compressed(compressed) if compressed.block == BlockFormat::Bc4 => {
+                gray_swizzle(gl::ONE)
+            }
+            Self::Compressed(_) => IDENTITY_SWIZZLE,
+        }
+    }
+}
+
+/// Decodes the image file in `bytes`, Radiance HDR files keep their float colors.
+pub(crate) fn decode_image(bytes: &[u8]) -> Result<DynamicImage, ImageError> {
+    if image::guess_format(bytes)? != ImageFormat::Hdr {
+        return image::load_from_memory(bytes);
+    }
+
+    // The image crate otherwise tone maps them to 8 bits.
+    let decoder = HdrDecoder::new(bytes)?;
+    let metadata = decoder.metadata();
+    let pixels = decoder.read_image_hdr()?;
+
+    Rgb32FImage::from_raw(
+        metadata.width,
+        metadata.height,
+        pixels.into_iter().flat_map(|pixel| pixel.0).collect(),
+    )
+    .map(DynamicImage::ImageRgb32F)
+    .ok_or_else(|| malformed("HDR", "the pixel count doesn't match the size"))
+}
+
+/// Uncompressed pixels, rows start at the top of the image and aren't padded.
+pub(crate) struct PixelData {
+    pub width: u32,
+    pub height: u32,
+    pub internal_format: gl::types::GLenum,
+    pub format: gl::types::GLenum,
+    pub ty: gl::types::GLenum,
+    pub bytes: Vec<u8>,
+    pub swizzle: [gl::types::GLenum; 4],
+}
+
+impl PixelData {
+    /// Keeps the channels and the precision of `img`. sRGB formats only exist with 8 bits per
+    /// channel, so 16-bit colors are converted to linear half floats instead.
+    pub fn from_image(img: &DynamicImage, color_space: ColorSpace) -> Self {
+        let srgb = color_space == ColorSpace::Srgb;
+        let (width, height) = (img.width(), img.height());
+        let data = |internal_format, format, ty, bytes| Self {
+            width,
+            height,
+            internal_format,
+            format,
+            ty,
+            bytes,
+            swizzle: IDENTITY_SWIZZLE,
+        };
+
+        match img {
+            // There is no single-channel sRGB format, and the alpha channel keeps the format
+            // renderable so mipmaps can be generated on GLES too.
+            DynamicImage::ImageLuma8(_)
+            | DynamicImage::ImageLumaA8(_)
+            | DynamicImage::ImageRgb8(_)
+            | DynamicImage::ImageRgba8(_)
+                if srgb =>
+            {
+                data(
+                    gl::SRGB8_ALPHA8,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    img.to_rgba8().into_raw(),
+                )
+            }
+            DynamicImage::ImageLuma8(gray) => Self {
+                swizzle: gray_swizzle(gl::ONE),
+                ..data(gl::R8, gl::RED, gl::UNSIGNED_BYTE, gray.as_raw().clone())
+            },
+            DynamicImage::ImageLumaA8(gray) => Self {
+                swizzle: gray_swizzle(gl::GREEN),
+                ..data(gl::RG8, gl::RG, gl::UNSIGNED_BYTE, gray.as_raw().clone())
+            },
+            DynamicImage::ImageRgb8(rgb) => {
+                data(gl::RGB8, gl::RGB, gl::UNSIGNED_BYTE, rgb.as_raw().clone())
+            }
+            DynamicImage::ImageLuma16(_)
+            | DynamicImage::ImageLumaA16(_)
+            | DynamicImage::ImageRgb16(_)
+            | DynamicImage::ImageRgba16(_)
+                if srgb =>
+            {
+                let linear = img
+                    .to_rgba32f()
+                    .pixels()
+                    .flat_map(|pixel| {
+                        let [r, g, b, a] = pixel.0;
+                        [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+                    })
+                    .collect::<Vec<_>>();
+                data(gl::RGBA16F, gl::RGBA, gl::FLOAT, f32_bytes(&linear))
+            }
+            DynamicImage::ImageLuma16(gray) => Self {
+                swizzle: gray_swizzle(gl::ONE),
+                ..data(R16, gl::RED, gl::UNSIGNED_SHORT, u16_bytes(gray.as_raw()))
+            },
+            DynamicImage::ImageLumaA16(gray) => Self {
+                swizzle: gray_swizzle(gl::GREEN),
+                ..data(RG16, gl::RG, gl::UNSIGNED_SHORT, u16_bytes(gray.as_raw()))
+            },
+            DynamicImage::ImageRgb16(rgb) => {
+                data(RGB16, gl::RGB, gl::UNSIGNED_SHORT, u16_bytes(rgb.as_raw()))
+            }
+            DynamicImage::ImageRgba16(rgba) => data(
+                RGBA16,
+                gl::RGBA,
+                gl::UNSIGNED_SHORT,
+                u16_bytes(rgba.as_raw()),
+            ),
+            // HDR and EXR images hold linear radiance whatever the color space.
+            DynamicImage::ImageRgb32F(rgb) => {
+                data(gl::RGB16F, gl::RGB, gl::FLOAT, f32_bytes(rgb.as_raw()))
+            }
+            DynamicImage::ImageRgba32F(rgba) => {
+                data(gl::RGBA16F, gl::RGBA, gl::FLOAT, f32_bytes(rgba.as_raw()))
+            }
+            _ => data(
+                if srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 },
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                img.to_rgba8().into_raw(),
+            ),
+        }
+    }
+}
+
+/// GPU block compression formats read from DDS and KTX2 files.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum BlockFormat {
+    /// DXT1.
+    Bc1,
+    /// DXT3.
+    Bc2,
+    /// DXT5.
+    Bc3,
+    /// One channel.
+    Bc4,
+    /// Two channels, usually a normal map.
+    Bc5,
+    Bc7,
+    Etc2Rgb,
+    Etc2Rgba,
+}
+
+impl BlockFormat {
+    /// Size of a 4x4 block.
+    fn block_bytes(self) -> usize {
+        match self {
+            Self::Bc1 | Self::Bc4 | Self::Etc2Rgb => 8,
+            Self::Bc2 | Self::Bc3 | Self::Bc5 | Self::Bc7 | Self::Etc2Rgba => 16,
+        }
+    }
+
+    pub fn internal_format(self, color_space: ColorSpace) -> gl::types::GLenum {
+        let srgb = color_space == ColorSpace::Srgb;
+
+        match self {
+            Self::Bc1 if srgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+            Self::Bc1 => COMPRESSED_RGBA_S3TC_DXT1,
+            Self::Bc2 if srgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT3,
+            Self::Bc2 => COMPRESSED_RGBA_S3TC_DXT3,
+            Self::Bc3 if srgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+            Self::Bc3 => COMPRESSED_RGBA_S3TC_DXT5,
+            Self::Bc4 => COMPRESSED_RED_RGTC1,
+            Self::Bc5 => COMPRESSED_RG_RGTC2,
+            Self::Bc7 if srgb => COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+            Self::Bc7 => COMPRESSED_RGBA_BPTC_UNORM,
+            Self::Etc2Rgb if srgb => gl::COMPRESSED_SRGB8_ETC2,
+            Self::Etc2Rgb => gl::COMPRESSED_RGB8_ETC2,
+            Self::Etc2Rgba if srgb => gl::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
+            Self::Etc2Rgba => gl::COMPRESSED_RGBA8_ETC2_EAC,
+        }
+    }
+
+    /// Extensions providing the format on drivers that don't list it as a general purpose
+    /// compressed format.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Bc1 | Self::Bc2 | Self::Bc3 => &["GL_EXT_texture_compression_s3tc"],
+            Self::Bc4 | Self::Bc5 => &[
+                "GL_ARB_texture_compression_rgtc",
+                "GL_EXT_texture_compression_rgtc",
+            ],
+            Self::Bc7 => &[
+                "GL_ARB_texture_compression_bptc",
+                "GL_EXT_texture_compression_bptc",
+            ],
+            Self::Etc2Rgb | Self::Etc2Rgba => &["GL_ARB_ES3_compatibility"],
+        }
+    }
+
+    /// Bytes of a `width` by `height` mip level, `None` when they don't fit in a `usize`.
+    fn level_size(self, width: u32, height: u32) -> Option<usize> {
+        (width.div_ceil(4) as usize)
+            .checked_mul(height.div_ceil(4) as usize)?
+            .checked_mul(self.block_bytes())
+    }
+}
+
+/// Compressed blocks of every mip level, the largest first.
+pub(crate) struct CompressedData {
+    pub width: u32,
+    pub height: u32,
+    pub block: BlockFormat,
+    pub internal_format: gl::types::GLenum,
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedData {
+    const DDS_MAGIC: &'static [u8] = b"DDS ";
+    const DDS_HEADER_SIZE: usize = 128;
+    const DDS_DX10_HEADER_SIZE: usize = 20;
+    /// `DDSD_MIPMAPCOUNT` of the header flags, the level count is garbage without it.
+    const DDS_MIPMAP_COUNT_FLAG: u32 = 0x2_0000;
+    /// `DDPF_FOURCC` of the pixel format flags.
+    const DDS_FOURCC_FLAG: u32 = 0x4;
+
+    const KTX2_MAGIC: &'static [u8] = b"\xABKTX 20\xBB\r\n\x1A\n";
+    const KTX2_HEADER_SIZE: usize = 80;
+    const KTX2_LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+    /// `None` when `bytes` isn't a DDS file or holds a format the image crate should handle.
+    fn parse_dds(bytes: &[u8], color_space: ColorSpace) -> Option<Result<Self, ImageError>> {
+        if !bytes.starts_with(Self::DDS_MAGIC) || bytes.len() < Self::DDS_HEADER_SIZE {
+            return None;
+        }
+
+        let height = read_u32(bytes, 12)?;
+        let width = read_u32(bytes, 16)?;
+        let level_count = if read_u32(bytes, 8)? & Self::DDS_MIPMAP_COUNT_FLAG != 0 {
+            read_u32(bytes, 28)?.max(1)
+        } else {
+            1
+        };
+        if read_u32(bytes, 80)? & Self::DDS_FOURCC_FLAG == 0 {
+            return None;
+        }
+
+        let (block, data_offset) = match &bytes[84..88] {
+            b"DXT1" => (BlockFormat::Bc1, Self::DDS_HEADER_SIZE),
+            b"DXT2" | b"DXT3" => (BlockFormat::Bc2, Self::DDS_HEADER_SIZE),
+            b"DXT4" | b"DXT5" => (BlockFormat::Bc3, Self::DDS_HEADER_SIZE),
+            b"ATI1" | b"BC4U" => (BlockFormat::Bc4, Self::DDS_HEADER_SIZE),
+            b"ATI2" | b"BC5U" => (BlockFormat::Bc5, Self::DDS_HEADER_SIZE),
+            b"DX10" => {
+                // DXGI_FORMAT values, the typeless, unorm and sRGB variants of each.
+                let block = match read_u32(bytes, Self::DDS_HEADER_SIZE)? {
+                    70..=72 => BlockFormat::Bc1,
+                    73..=75 => BlockFormat::Bc2,
+                    76..=78 => BlockFormat::Bc3,
+                    79 | 80 => BlockFormat::Bc4,
+                    82 | 83 => BlockFormat::Bc5,
+                    97..=99 => BlockFormat::Bc7,
+                    _ => return None,
+                };
+                (block, Self::DDS_HEADER_SIZE + Self::DDS_DX10_HEADER_SIZE)
+            }
+            _ => return None,
+        };
+
+        if let Err(err) = Self::check_size("DDS", width, height, level_count) {
+            return Some(Err(err));
+        }
+
+        let mut levels = Vec::with_capacity(level_count as usize);
+        let mut offset = data_offset;
+        for level in 0..level_count {
+            let (level_width, level_height) = Self::level_dimensions(width, height, level);
+            let Some(end) = block
+                .level_size(level_width, level_height)
+                .and_then(|size| offset.checked_add(size))
+            else {
+                return Some(Err(malformed("DDS", "the image is too large")));
+            };
+            let Some(data) = bytes.get(offset..end) else {
+                return Some(Err(malformed(
+                    "DDS",
+                    "the file ends before its last mip level",
+                )));
+            };
+            levels.push(data.to_vec());
+            offset = end;
+        }
+
+        Some(Ok(Self {
+            width,
+            height,
+            block,
+            internal_format: block.internal_format(color_space),
+            levels,
+        }))
+    }
+
+    /// Rejects empty images and mip chains going past the 1x1 level, which would also make
+    /// the level dimensions shift by 32 bits or more.
+    fn check_size(
+        format: &str,
+        width: u32,
+        height: u32,
+        level_count: u32,
+    ) -> Result<(), ImageError> {
+        if width == 0 || height == 0 {
+            return Err(malformed(format, "the image is empty"));
+        }
+        if level_count > u32::BITS - width.max(height).leading_zeros() {
+            return Err(malformed(
+                format,
+                "there are more mip levels than down to 1x1",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Size of mip `level` of a `width` by `height` image, never below 1x1.
+    pub fn level_dimensions(width: u32, height: u32, level: u32) -> (u32, u32) {
+        let shrink = |size: u32| size.checked_shr(level).unwrap_or(0).max(1);
+
+        (shrink(width), shrink(height))
+    }
+
+    /// `None` when `bytes` isn't a KTX2 file.
+    fn parse_ktx2(bytes: &[u8], color_space: ColorSpace) -> Option<Result<Self, ImageError>> {
+        if !bytes.starts_with(Self::KTX2_MAGIC) {
+            return None;
+        }
+
+        Some(Self::parse_ktx2_header(bytes, color_space))
+    }
+
+    fn parse_ktx2_header(bytes: &[u8], color_space: ColorSpace) -> Result<Self, ImageError> {
+        let truncated = || malformed("KTX2", "the header is truncated");
+        let field = |offset| read_u32(bytes, offset).ok_or_else(truncated);
+
+        let vk_format = field(12)?;
+        let width = field(20)?;
+        // 0 for 1D textures.
+        let height = field(24)?.max(1);
+        let level_count = field(40)?.max(1);
+        let supercompression = field(44)?;
+
+        Self::check_size("KTX2", width, height, level_count)?;
+
+        if supercompression != 0 {
+            return Err(unsupported(
+                "KTX2",
+                format!("supercompression scheme {supercompression}"),
+            ));
+        }
+
+        // VkFormat values, the unorm and sRGB variants of each.
+        let block = match vk_format {
+            131..=134 => BlockFormat::Bc1,
+            135 | 136 => BlockFormat::Bc2,
+            137 | 138 => BlockFormat::Bc3,
+            139 => BlockFormat::Bc4,
+            141 => BlockFormat::Bc5,
+            145 | 146 => BlockFormat::Bc7,
+            147 | 148 => BlockFormat::Etc2Rgb,
+            151 | 152 => BlockFormat::Etc2Rgba,
+            _ => return Err(unsupported("KTX2", format!("VkFormat {vk_format}"))),
+        };
+
+        let levels = (0..level_count as usize)
+            .map(|level| {
+                let entry = Self::KTX2_HEADER_SIZE + level * Self::KTX2_LEVEL_INDEX_ENTRY_SIZE;
+                let offset = read_u64(bytes, entry).ok_or_else(truncated)? as usize;
+                let length = read_u64(bytes, entry + 8).ok_or_else(truncated)? as usize;
+
+                offset
+                    .checked_add(length)
+                    .and_then(|end| bytes.get(offset..end))
+                    .map(<[u8]>::to_vec)
+                    .ok_or_else(|| malformed("KTX2", "a mip level lies outside of the file"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            width,
+            height,
+            block,
+            internal_format: block.internal_format(color_space),
+            levels,
+        })
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn malformed(format: &str, problem: &'static str) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name(format.to_owned()),
+        problem,
+    ))
+}
+
+fn unsupported(format: &str, feature: String) -> ImageError {
+    ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+        ImageFormatHint::Name(format.to_owned()),
+        UnsupportedErrorKind::GenericFeature(feature),
+    ))
+}
+
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn u16_bytes(values: &[u16]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_ne_bytes())
+        .collect()
+}
+
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_ne_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{ImageOutputFormat, Rgb, Rgba, Rgba32FImage};
+
+    use super::*;
+
+    fn encode(img: &DynamicImage, format: ImageOutputFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        img.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    fn decode(bytes: &[u8], color_space: ColorSpace) -> TextureData {
+        TextureData::decode(bytes, color_space, |_| true).unwrap()
+    }
+
+    fn pixels(data: TextureData) -> PixelData {
+        match data {
+            TextureData::Pixels(pixels) => pixels,
+            TextureData::Compressed(_) => panic!("expected uncompressed pixels"),
+        }
+    }
+
+    fn compressed(data: TextureData) -> CompressedData {
+        match data {
+            TextureData::Compressed(compressed) => compressed,
+            TextureData::Pixels(_) => panic!("expected compressed blocks"),
+        }
+    }
+
+    fn dds(four_cc: &[u8; 4], width: u32, height: u32, levels: u32, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0; CompressedData::DDS_HEADER_SIZE];
+        bytes[..4].copy_from_slice(CompressedData::DDS_MAGIC);
+        bytes[4..8].copy_from_slice(&124u32.to_le_bytes());
+        // DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT | DDSD_MIPMAPCOUNT
+        bytes[8..12].copy_from_slice(&0x2_1007u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[28..32].copy_from_slice(&levels.to_le_bytes());
+        bytes[76..80].copy_from_slice(&32u32.to_le_bytes());
+        bytes[80..84].copy_from_slice(&CompressedData::DDS_FOURCC_FLAG.to_le_bytes());
+        bytes[84..88].copy_from_slice(four_cc);
+        // DDSCAPS_TEXTURE
+        bytes[108..112].copy_from_slice(&0x1000u32.to_le_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    fn ktx2(vk_format: u32, width: u32, height: u32, levels: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = vec![0; CompressedData::KTX2_HEADER_SIZE];
+        bytes[..12].copy_from_slice(CompressedData::KTX2_MAGIC);
+        bytes[12..16].copy_from_slice(&vk_format.to_le_bytes());
+        bytes[16..20].copy_from_slice(&1u32.to_le_bytes());
+        bytes[20..24].copy_from_slice(&width.to_le_bytes());
+        bytes[24..28].copy_from_slice(&height.to_le_bytes());
+        bytes[36..40].copy_from_slice(&1u32.to_le_bytes());
+        bytes[40..44].copy_from_slice(&(levels.len() as u32).to_le_bytes());
+
+        let mut offset = bytes.len() + levels.len() * CompressedData::KTX2_LEVEL_INDEX_ENTRY_SIZE;
+        for level in levels {
+            bytes.extend_from_slice(&(offset as u64).to_le_bytes());
+            bytes.extend_from_slice(&(level.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&(level.len() as u64).to_le_bytes());
+            offset += level.len();
+        }
+        for level in levels {
+            bytes.extend_from_slice(level);
+        }
+        bytes
+    }
+
+    #[test]
+    fn rgba8_keeps_alpha() {
+        let img =
+            DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(3, 2, Rgba([10, 20, 30, 40])));
+        let png = encode(&img, ImageOutputFormat::Png);
+
+        let srgb = pixels(decode(&png, ColorSpace::Srgb));
+        assert_eq!(srgb.internal_format, gl::SRGB8_ALPHA8);
+        assert_eq!(srgb.bytes[..4], [10, 20, 30, 40]);
+        assert_eq!(srgb.bytes.len(), 3 * 2 * 4);
+
+        let linear = pixels(decode(&png, ColorSpace::Linear));
+        assert_eq!(linear.internal_format, gl::RGBA8);
+        assert_eq!((linear.format, linear.ty), (gl::RGBA, gl::UNSIGNED_BYTE));
+    }
+
+    #[test]
+    fn rgb8_jpeg() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(8, 8, Rgb([200, 100, 50])));
+        let jpeg = encode(&img, ImageOutputFormat::Jpeg(90));
+
+        let srgb = pixels(decode(&jpeg, ColorSpace::Srgb));
+        assert_eq!(srgb.internal_format, gl::SRGB8_ALPHA8);
+        assert_eq!(srgb.bytes.len(), 8 * 8 * 4);
+        assert_eq!(
+            pixels(decode(&jpeg, ColorSpace::Linear)).internal_format,
+            gl::RGB8
+        );
+    }
+
+    #[test]
+    fn sixteen_bit_png() {
+        let img = DynamicImage::ImageRgb16(image::ImageBuffer::from_pixel(
+            2,
+            2,
+            Rgb([0xFFFF, 0x8000, 0x0102]),
+        ));
+        let png = encode(&img, ImageOutputFormat::Png);
+
+        let linear = pixels(decode(&png, ColorSpace::Linear));
+        assert_eq!(linear.internal_format, RGB16);
+        assert_eq!(linear.ty, gl::UNSIGNED_SHORT);
+        assert_eq!(linear.bytes[4..6], 0x0102u16.to_ne_bytes());
+
+        // No 16-bit sRGB format, decoded to linear half floats on the CPU.
+        let srgb = pixels(decode(&png, ColorSpace::Srgb));
+        assert_eq!(srgb.internal_format, gl::RGBA16F);
+        assert_eq!(srgb.ty, gl::FLOAT);
+        let green = f32::from_ne_bytes(srgb.bytes[4..8].try_into().unwrap());
+        assert!((green - 0.214).abs() < 1e-3, "{green}");
+    }
+
+    #[test]
+    fn grayscale_png() {
+        let gray = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(4, 4, image::Luma([7])));
+        let png = encode(&gray, ImageOutputFormat::Png);
+
+        let linear = pixels(decode(&png, ColorSpace::Linear));
+        assert_eq!((linear.internal_format, linear.format), (gl::R8, gl::RED));
+        assert_eq!(linear.swizzle, gray_swizzle(gl::ONE));
+        assert_eq!(linear.bytes, vec![7; 16]);
+
+        let srgb = pixels(decode(&png, ColorSpace::Srgb));
+        assert_eq!(srgb.internal_format, gl::SRGB8_ALPHA8);
+        assert_eq!(srgb.bytes[..4], [7, 7, 7, 255]);
+
+        let gray_alpha =
+            DynamicImage::ImageLumaA16(image::ImageBuffer::from_pixel(1, 1, image::LumaA([1, 2])));
+        let png = encode(&gray_alpha, ImageOutputFormat::Png);
+        let linear = pixels(decode(&png, ColorSpace::Linear));
+        assert_eq!((linear.internal_format, linear.format), (RG16, gl::RG));
+        assert_eq!(linear.swizzle, gray_swizzle(gl::GREEN));
+    }
+
+    #[test]
+    fn radiance_hdr_keeps_values_above_one() {
+        let img = Rgb32FImage::from_pixel(2, 2, Rgb([8.0, 1.0, 0.25]));
+        let mut hdr = Vec::new();
+        image::codecs::hdr::HdrEncoder::new(&mut hdr)
+            .encode(&img.pixels().copied().collect::<Vec<_>>(), 2, 2)
+            .unwrap();
+
+        // Always linear, the color space only applies to low dynamic range images.
+        let data = pixels(decode(&hdr, ColorSpace::Srgb));
+        assert_eq!(data.internal_format, gl::RGB16F);
+        assert_eq!((data.format, data.ty), (gl::RGB, gl::FLOAT));
+        let red = f32::from_ne_bytes(data.bytes[..4].try_into().unwrap());
+        assert!((red - 8.0).abs() < 0.1, "{red}");
+    }
+
+    #[test]
+    fn openexr_with_alpha() {
+        let img =
+            DynamicImage::ImageRgba32F(Rgba32FImage::from_pixel(2, 1, Rgba([2.5, 0.5, 0.0, 0.75])));
+        let exr = encode(&img, ImageOutputFormat::OpenExr);
+
+        let data = pixels(decode(&exr, ColorSpace::Linear));
+        assert_eq!(data.internal_format, gl::RGBA16F);
+        assert_eq!(data.bytes.len(), 2 * 4 * 4);
+        let alpha = f32::from_ne_bytes(data.bytes[12..16].try_into().unwrap());
+        assert_eq!(alpha, 0.75);
+    }
+
+    #[test]
+    fn dds_keeps_blocks_and_mip_levels() {
+        // 8x8 with two levels: four blocks and one.
+        let file = dds(b"DXT5", 8, 8, 2, &[0xAB; 5 * 16]);
+
+        let data = compressed(decode(&file, ColorSpace::Srgb));
+        assert_eq!(data.block, BlockFormat::Bc3);
+        assert_eq!(data.internal_format, COMPRESSED_SRGB_ALPHA_S3TC_DXT5);
+        assert_eq!((data.width, data.height), (8, 8));
+        assert_eq!(
+            data.levels.iter().map(Vec::len).collect::<Vec<_>>(),
+            [64, 16]
+        );
+
+        let truncated = &file[..file.len() - 1];
+        assert!(TextureData::decode(truncated, ColorSpace::Srgb, |_| true).is_err());
+    }
+
+    #[test]
+    fn dds_mip_chain_stops_at_one_pixel() {
+        // 4x4 has three levels, each a single block.
+        let file = dds(b"DXT1", 4, 4, 3, &[0; 3 * 8]);
+        assert_eq!(compressed(decode(&file, ColorSpace::Srgb)).levels.len(), 3);
+
+        for levels in [4, 40, u32::MAX] {
+            let file = dds(b"DXT1", 4, 4, levels, &[0; 64 * 8]);
+            assert!(TextureData::decode(&file, ColorSpace::Srgb, |_| true).is_err());
+        }
+
+        // Without DDSD_MIPMAPCOUNT the level count is ignored.
+        let mut file = dds(b"DXT1", 4, 4, u32::MAX, &[0; 8]);
+        file[8..12].copy_from_slice(&0x1007u32.to_le_bytes());
+        assert_eq!(compressed(decode(&file, ColorSpace::Srgb)).levels.len(), 1);
+    }
+
+    #[test]
+    fn dds_too_large_to_address_fails() {
+        // 2^30 by 2^30 blocks of 16 bytes overflow a 64-bit size.
+        let file = dds(b"DXT5", u32::MAX, u32::MAX, 1, &[0; 16]);
+
+        let err = TextureData::decode(&file, ColorSpace::Srgb, |_| true).err();
+        assert!(err.is_some_and(|err| err.to_string().contains("the image is too large")));
+    }
+
+    #[test]
+    fn dds_without_driver_support_is_decompressed() {
+        // A single DXT1 block: both endpoint colors white, every index 0.
+        let block = [0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0];
+        let file = dds(b"DXT1", 4, 4, 1, &block);
+
+        let data = TextureData::decode(&file, ColorSpace::Linear, |_| false).unwrap();
+        let data = pixels(data);
+        assert_eq!(data.internal_format, gl::RGB8);
+        assert_eq!(data.bytes, vec![0xFF; 4 * 4 * 3]);
+    }
+
+    #[test]
+    fn ktx2_compressed_levels() {
+        let level0 = [1; 4 * 16];
+        let level1 = [2; 16];
+        // VK_FORMAT_BC7_UNORM_BLOCK
+        let file = ktx2(145, 8, 8, &[&level0, &level1]);
+
+        let data = compressed(decode(&file, ColorSpace::Linear));
+        assert_eq!(data.block, BlockFormat::Bc7);
+        assert_eq!(data.internal_format, COMPRESSED_RGBA_BPTC_UNORM);
+        assert_eq!(data.levels, [level0.to_vec(), level1.to_vec()]);
+
+        // VK_FORMAT_ETC2_R8G8B8_SRGB_BLOCK
+        let file = ktx2(148, 4, 4, &[&[0; 8]]);
+        let data = compressed(decode(&file, ColorSpace::Srgb));
+        assert_eq!(data.internal_format, gl::COMPRESSED_SRGB8_ETC2);
+    }
+
+    #[test]
+    fn ktx2_unsupported_payloads_fail() {
+        // Rejected by the driver, KTX2 can't be decompressed on the CPU.
+        let file = ktx2(145, 4, 4, &[&[0; 16]]);
+        assert!(TextureData::decode(&file, ColorSpace::Linear, |_| false).is_err());
+
+        // VK_FORMAT_R8G8B8A8_UNORM isn't a block format.
+        let file = ktx2(37, 1, 1, &[&[0; 4]]);
+        assert!(TextureData::decode(&file, ColorSpace::Linear, |_| true).is_err());
+
+        let file = ktx2(145, 0, 4, &[&[0; 16]]);
+        assert!(TextureData::decode(&file, ColorSpace::Linear, |_| true).is_err());
+
+        // A 4x4 image only has three levels.
+        let file = ktx2(145, 4, 4, &[&[0u8; 16] as &[u8]; 4]);
+        assert!(TextureData::decode(&file, ColorSpace::Linear, |_| true).is_err());
+
+        let mut file = ktx2(145, 4, 4, &[&[0; 16]]);
+        // A level offset running past the end of the address space.
+        file[80..88].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(TextureData::decode(&file, ColorSpace::Linear, |_| true).is_err());
+
+        let mut file = ktx2(145, 4, 4, &[&[0; 16]]);
+        // Zstandard supercompression.
+        file[44..48].copy_from_slice(&2u32.to_le_bytes());
+        assert!(TextureData::decode(&file, ColorSpace::Linear, |_| true).is_err());
+    }
+}
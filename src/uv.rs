@@ -0,0 +1,92 @@
+use glam::{Mat3, Vec2};
+use serde::Deserialize;
+
+use crate::shader_program::ShaderProgram;
+
+/// How texture coordinates are derived from the surface. Values must match the
+/// `PROJECTION_*` defines in `polygon_program/lighting.glsl`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum UvProjection {
+    /// Along the azimuth and polar angle grid the figure is built from.
+    Parametric = 0,
+    Spherical = 1,
+    /// Around the vertical axis.
+    Cylindrical = 2,
+    /// Along [`UvMapping::planar_axis`].
+    Planar = 3,
+    /// Planar projections along all three axes blended by the normal.
+    Triplanar = 4,
+}
+
+impl UvProjection {
+    pub const ALL: [Self; 5] = [
+        Self::Parametric,
+        Self::Spherical,
+        Self::Cylindrical,
+        Self::Planar,
+        Self::Triplanar,
+    ];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Axis {
+    X = 0,
+    Y = 1,
+    Z = 2,
+}
+
+impl Axis {
+    pub fn next(self) -> Self {
+        match self {
+            Self::X => Self::Y,
+            Self::Y => Self::Z,
+            Self::Z => Self::X,
+        }
+    }
+}
+
+/// Texture coordinates shared by the classic and PBR maps.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct UvMapping {
+    pub projection: UvProjection,
+    pub planar_axis: Axis,
+    /// Times the texture repeats across the projection.
+    pub scale: Vec2,
+    pub offset: Vec2,
+    /// Counterclockwise rotation around the center of the texture in radians.
+    pub rotation: f32,
+}
+
+impl UvMapping {
+    /// Sets the uniforms of a program including `lighting.glsl`.
+    pub fn upload(&self, program: &ShaderProgram) {
+        program.set_uniform_i32("uUvProjection", self.projection as i32);
+        program.set_uniform_i32("uUvPlanarAxis", self.planar_axis as i32);
+        program.set_uniform_mat3("uUvTransform", self.transform().to_cols_array());
+    }
+
+    /// Scales and rotates around the center of the texture, then offsets.
+    fn transform(&self) -> Mat3 {
+        let center = Vec2::splat(0.5);
+
+        Mat3::from_translation(center + self.offset)
+            * Mat3::from_angle(self.rotation)
+            * Mat3::from_scale(self.scale)
+            * Mat3::from_translation(-center)
+    }
+}
+
+impl Default for UvMapping {
+    fn default() -> Self {
+        Self {
+            projection: UvProjection::Parametric,
+            planar_axis: Axis::Z,
+            scale: Vec2::ONE,
+            offset: Vec2::ZERO,
+            rotation: 0.0,
+        }
+    }
+}
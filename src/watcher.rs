@@ -0,0 +1,54 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Polls the modification time of a file so changes can be picked up while running.
+pub(crate) struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    since_last_check: f32,
+}
+
+impl FileWatcher {
+    /// Seconds between two looks at the file.
+    const CHECK_INTERVAL: f32 = 0.5;
+
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+
+        Self {
+            modified: modified_time(&path),
+            path,
+            since_last_check: 0.0,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file has changed since the last call, `dt` is the time in seconds since the
+    /// last call.
+    pub fn poll(&mut self, dt: f32) -> bool {
+        self.since_last_check += dt;
+        if self.since_last_check < Self::CHECK_INTERVAL {
+            return false;
+        }
+        self.since_last_check = 0.0;
+
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+
+        true
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}